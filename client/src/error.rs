@@ -0,0 +1,45 @@
+// Ошибки клиента: подключение к серверу и работа окна.
+// Ошибки обмена с сервером показываются в окне и записываются в журнал, клиент продолжает работу
+use std::fmt;
+use std::io;
+
+use protocol::tls::TlsError;
+use protocol::RejectReason;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Connect(io::Error),    // Сервер недоступен
+    Tls(TlsError),         // TLS-соединение не установлено (в том числе сертификат не прошёл проверку)
+    Busy(u64),             // Сервер заполнен; повторить подключение через указанное число мс
+    Handshake(String),     // Подключение отклонено или приветствие не удалось
+    Unauthorized(String),  // Сервер не опознал клиента по токену или сертификату
+    Rejected(RejectReason, String, Option<u64>), // Адрес запрещён или превышена частота; повтор через мс, если возможен
+    Window(eframe::Error), // Не удалось открыть окно
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Connect(e) => write!(f, "сервер недоступен: {}", e),
+            ClientError::Tls(e) => write!(f, "{}", e),
+            ClientError::Busy(retry_after_ms) => {
+                write!(f, "сервер заполнен, повторная попытка через {} с", retry_after_ms.div_ceil(1000))
+            }
+            ClientError::Handshake(message) => write!(f, "{}", message),
+            ClientError::Unauthorized(message) => write!(f, "доступ запрещён: {}", message),
+            ClientError::Rejected(_, message, _) => write!(f, "сервер отказал в обслуживании: {}", message),
+            ClientError::Window(e) => write!(f, "не удалось открыть окно: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Connect(e) => Some(e),
+            ClientError::Tls(e) => Some(e),
+            ClientError::Window(e) => Some(e),
+            ClientError::Busy(_) | ClientError::Handshake(_) | ClientError::Unauthorized(_) | ClientError::Rejected(..) => None,
+        }
+    }
+}
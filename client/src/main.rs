@@ -1,63 +1,158 @@
+mod error;
+
 use eframe::egui;
-use std::sync::{Arc, Mutex};
+use error::ClientError;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::thread;
-use std::net::{TcpStream, Shutdown};
-use std::io::{Read, Write};
-use std::fs::OpenOptions;
-use serde_json::Value;
-use ctrlc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::net::TcpStream;
+use std::path::Path;
+use logging::{LogFormat, Logging, Rotation};
+use protocol::tls::{client_config, load_certificates, load_private_key, parse_fingerprint, ClientConfig, ClientIdentity, Connection, ServerTrust};
+use protocol::{read_message, write_message, AuthToken, HostInfo, PointingDevice, ProcessDetails, ProcessInfo, Request, Response, ServerKind, PROTOCOL_VERSION};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use socket2::{SockRef, TcpKeepalive};
 use std::sync::mpsc;
 use chrono::DateTime;
+use tracing::{debug, error, info, warn};
+
+// Период проверки команд интерфейса, пока нет данных от сервера
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Проверка связи: Ping отправляется, если серверу ничего не отправлялось дольше HEARTBEAT_INTERVAL
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+// Молчание сервера, после которого соединение считается оборванным
+const PEER_TIMEOUT: Duration = Duration::from_secs(30);
+// Предельная длительность отправки одного запроса
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+// Простой соединения до первой проверки TCP keepalive
+const KEEPALIVE_TIME: Duration = Duration::from_secs(15);
+// Максимальное число хранимых событий сервера
+const MAX_EVENTS: usize = 100;
+// Журнал клиента: файл и переменные окружения для фильтра уровней, формата и ротации
+const LOG_FILE: &str = "client_log.txt";
+const LOG_LEVEL_ENV: &str = "CLIENT_LOG_LEVEL";
+const LOG_FORMAT_ENV: &str = "CLIENT_LOG_FORMAT";
+const LOG_MAX_SIZE_ENV: &str = "CLIENT_LOG_MAX_SIZE_BYTES";
+const LOG_ROTATION_ENV: &str = "CLIENT_LOG_ROTATION";
+const LOG_KEEP_ENV: &str = "CLIENT_LOG_KEEP";
+const LOG_COMPRESS_ENV: &str = "CLIENT_LOG_COMPRESS";
+// TLS: сертификат CA в формате PEM или отпечатки SHA-256 сертификатов серверов через запятую,
+// а также имя для проверки сертификата, если оно отличается от адреса сервера
+const TLS_CA_ENV: &str = "CLIENT_TLS_CA";
+const TLS_FINGERPRINT_ENV: &str = "CLIENT_TLS_FINGERPRINT";
+const TLS_SERVER_NAME_ENV: &str = "CLIENT_TLS_SERVER_NAME";
+// Проверка подлинности клиента сервером: токен в приветствии или сертификат клиента с ключом (PEM)
+const TOKEN_ENV: &str = "CLIENT_TOKEN";
+const TLS_CERT_ENV: &str = "CLIENT_TLS_CERT";
+const TLS_KEY_ENV: &str = "CLIENT_TLS_KEY";
+
+// Команды потоку обмена с сервером
+enum ServerCommand {
+    Get,                 // Разовый запрос данных
+    Subscribe(u64),      // Изменение интервала подписки, мс
+    FindProcess(String), // Поиск процесса по PID или имени (сервер 2)
+    HostInfo,            // Запрос сведений о системе (сервер 2)
+    Stop,                // Отключение от сервера
+}
+
+// Настройки TLS-соединений с серверами
+#[derive(Clone)]
+struct TlsSettings {
+    config: Arc<ClientConfig>,
+    server_name: Option<String>, // Имя из сертификата сервера; None - адрес, по которому идёт подключение
+}
+
+// Параметры подключения к серверам
+#[derive(Clone, Default)]
+struct ConnectOptions {
+    tls: Option<TlsSettings>, // None - соединения без шифрования
+    token: Option<AuthToken>, // Токен для серверов, проверяющих подлинность клиентов
+}
+
+// Отображаемые данные сервера, заполняемые потоком обмена
+#[derive(Clone)]
+struct ServerView {
+    data: Arc<Mutex<String>>,                 // Текстовое представление последних данных
+    response: Arc<Mutex<Option<Response>>>,   // Последний полученный ответ с данными
+    events: Arc<Mutex<Vec<String>>>,          // События сервера (подключение устройств)
+    lookup: Arc<Mutex<Option<Response>>>,     // Результат последнего поиска процессов
+    host: Arc<Mutex<Option<HostInfo>>>,       // Сведения о системе сервера 2
+    instance: Arc<Mutex<Option<ProcessInfo>>>, // Последние сведения о запуске сервера 2 (сохраняются при переподключении)
+    denied: Arc<Mutex<Option<String>>>,       // Причина отказа сервера в доступе
+}
+
+impl ServerView {
+    fn new() -> Self {
+        ServerView {
+            data: Arc::new(Mutex::new("Нет данных".to_owned())),
+            response: Arc::new(Mutex::new(None)),
+            events: Arc::new(Mutex::new(Vec::new())),
+            lookup: Arc::new(Mutex::new(None)),
+            host: Arc::new(Mutex::new(None)),
+            instance: Arc::new(Mutex::new(None)),
+            denied: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn clear(&self) {
+        *lock(&self.data) = "Нет данных".to_string();
+        *lock(&self.response) = None;
+        lock(&self.events).clear();
+        *lock(&self.lookup) = None;
+        *lock(&self.host) = None;
+        *lock(&self.denied) = None;
+    }
+}
 
 struct ClientApp {
-    server1_data: Arc<Mutex<String>>,
-    server2_data: Arc<Mutex<String>>,
+    server1_view: ServerView,
+    server2_view: ServerView,
     server1_ip: String,
     server2_ip: String,
     status_message: Arc<Mutex<String>>,
-    log_sender: mpsc::Sender<String>,
     connected_to_server1: bool,
     connected_to_server2: bool,
-    server1_stop_sender: Option<mpsc::Sender<()>>,
-    server2_stop_sender: Option<mpsc::Sender<()>>,
+    server1_command_sender: Option<mpsc::Sender<ServerCommand>>,
+    server2_command_sender: Option<mpsc::Sender<ServerCommand>>,
     server1_error: Arc<Mutex<bool>>,
     server2_error: Arc<Mutex<bool>>,
     server1_error_logged: Arc<Mutex<bool>>,
     server2_error_logged: Arc<Mutex<bool>>,
-    client_id: u128,
+    client_id: u64,
+    options: ConnectOptions,
+    interval_ms: u64,            // Интервал подписки на данные серверов, мс
+    subscribed_interval_ms: u64, // Интервал последней переподписки подключенных серверов
+    process_query: String,       // PID или имя процесса для поиска на сервере 2
 }
 
-// Реализация по умолчанию для ClientApp
-impl Default for ClientApp {
-    fn default() -> Self {
-        let (log_sender, log_receiver) = mpsc::channel(); // Создание канала для логирования
-        thread::spawn(move || logging_client(log_receiver)); // Запуск потока для логирования
-
+impl ClientApp {
+    fn new(options: ConnectOptions) -> Self {
         let client_id = SystemTime::now()
             .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis(); // Получение идентификатора клиента
+            .unwrap_or_default()
+            .as_millis() as u64; // Получение идентификатора клиента
 
-        let log_sender_clone = log_sender.clone();
-        log_sender_clone.send(format!("Клиент запущен. ID клиента: {}", client_id)).unwrap();
+        info!(client_id, "Клиент запущен");
 
         Self {
-            server1_data: Arc::new(Mutex::new("Нет данных".to_owned())),
-            server2_data: Arc::new(Mutex::new("Нет данных".to_owned())),
+            server1_view: ServerView::new(),
+            server2_view: ServerView::new(),
             server1_ip: "127.0.0.1:7878".to_string(),
             server2_ip: "127.0.0.1:7879".to_string(),
             status_message: Arc::new(Mutex::new("Готов".to_string())),
-            log_sender,
             connected_to_server1: false,
             connected_to_server2: false,
-            server1_stop_sender: None,
-            server2_stop_sender: None,
+            server1_command_sender: None,
+            server2_command_sender: None,
             server1_error: Arc::new(Mutex::new(false)),
             server2_error: Arc::new(Mutex::new(false)),
             server1_error_logged: Arc::new(Mutex::new(false)),
             server2_error_logged: Arc::new(Mutex::new(false)),
             client_id,
+            options,
+            interval_ms: 10_000,
+            subscribed_interval_ms: 10_000,
+            process_query: String::new(),
         }
     }
 }
@@ -69,40 +164,40 @@ impl eframe::App for ClientApp {
             ui.heading("Курсовая работа (Вариант 6)"); // Заголовок окна
 
             ui.horizontal(|ui| {
-                if ui.button("Подключиться к серверу 1").clicked() {
-                    if !self.connected_to_server1 {
-                        let ip = self.server1_ip.clone();
-                        let data = Arc::clone(&self.server1_data);
-                        let status = Arc::clone(&self.status_message);
-                        let log_sender = self.log_sender.clone();
-                        let server_name = "сервер 1".to_string();
-                        let error_flag = Arc::clone(&self.server1_error);
-                        let error_logged = Arc::clone(&self.server1_error_logged);
-
-                        let (_handle, stop_sender) = get_server_data_async(
-                            ip, data, status, log_sender, server_name, error_flag, error_logged, self.client_id
-                        );
-                        self.server1_stop_sender = Some(stop_sender); // Установка отправителя для остановки первого сервера
-                        self.connected_to_server1 = true;
-                    }
+                if ui.button("Подключиться к серверу 1").clicked() && !self.connected_to_server1 {
+                    let ip = self.server1_ip.clone();
+                    let view = self.server1_view.clone();
+                    let status = Arc::clone(&self.status_message);
+                    let server_name = "сервер 1".to_string();
+                    let server_kind = ServerKind::MouseInfo;
+                    let error_flag = Arc::clone(&self.server1_error);
+                    let error_logged = Arc::clone(&self.server1_error_logged);
+
+                    let options = self.options.clone();
+
+                    let (_handle, command_sender) = get_server_data_async(
+                        ip, options, view, status, server_name, server_kind, error_flag, error_logged, self.client_id, self.interval_ms
+                    );
+                    self.server1_command_sender = Some(command_sender); // Установка отправителя команд первому серверу
+                    self.connected_to_server1 = true;
                 }
 
-                if ui.button("Подключиться к серверу 2").clicked() {
-                    if !self.connected_to_server2 {
-                        let ip = self.server2_ip.clone();
-                        let data = Arc::clone(&self.server2_data);
-                        let status = Arc::clone(&self.status_message);
-                        let log_sender = self.log_sender.clone();
-                        let server_name = "сервер 2".to_string();
-                        let error_flag = Arc::clone(&self.server2_error);
-                        let error_logged = Arc::clone(&self.server2_error_logged);
-
-                        let (_handle, stop_sender) = get_server_data_async(
-                            ip, data, status, log_sender, server_name, error_flag, error_logged, self.client_id
-                        );
-                        self.server2_stop_sender = Some(stop_sender); // Установка отправителя для остановки второго сервера
-                        self.connected_to_server2 = true;
-                    }
+                if ui.button("Подключиться к серверу 2").clicked() && !self.connected_to_server2 {
+                    let ip = self.server2_ip.clone();
+                    let view = self.server2_view.clone();
+                    let status = Arc::clone(&self.status_message);
+                    let server_name = "сервер 2".to_string();
+                    let server_kind = ServerKind::ProcessInfo;
+                    let error_flag = Arc::clone(&self.server2_error);
+                    let error_logged = Arc::clone(&self.server2_error_logged);
+
+                    let options = self.options.clone();
+
+                    let (_handle, command_sender) = get_server_data_async(
+                        ip, options, view, status, server_name, server_kind, error_flag, error_logged, self.client_id, self.interval_ms
+                    );
+                    self.server2_command_sender = Some(command_sender); // Установка отправителя команд второму серверу
+                    self.connected_to_server2 = true;
                 }
 
                 if ui.button("Отключиться от сервера 1").clicked() {
@@ -114,236 +209,833 @@ impl eframe::App for ClientApp {
                 }
             });
 
+            ui.horizontal(|ui| {
+                ui.label("Интервал обновления, мс:");
+                let interval = ui.add(egui::DragValue::new(&mut self.interval_ms).range(100..=60_000).speed(100));
+                // Переподписка подключенных серверов с новым интервалом после окончания перетаскивания
+                // или ввода значения, а не на каждом его шаге
+                let finished = interval.drag_stopped() || interval.lost_focus();
+                if finished && self.interval_ms != self.subscribed_interval_ms {
+                    self.subscribed_interval_ms = self.interval_ms;
+                    for sender in [&self.server1_command_sender, &self.server2_command_sender].into_iter().flatten() {
+                        let _ = sender.send(ServerCommand::Subscribe(self.interval_ms));
+                    }
+                }
+
+                if ui.button("Запросить сервер 1").clicked() {
+                    if let Some(sender) = &self.server1_command_sender {
+                        let _ = sender.send(ServerCommand::Get);
+                    }
+                }
+
+                if ui.button("Запросить сервер 2").clicked() {
+                    if let Some(sender) = &self.server2_command_sender {
+                        let _ = sender.send(ServerCommand::Get);
+                    }
+                }
+            });
+
             ui.separator(); // Разделитель
 
             // Данные о серверах
             egui::ScrollArea::vertical().show(ui, |ui| {
                 ui.label("Сервер 1:");
-                if *self.server1_error.lock().unwrap() {
+                if let Some(reason) = &*lock(&self.server1_view.denied) {
+                    ui.colored_label(egui::Color32::RED, format!("Доступ запрещён: {}", reason));
+                } else if *lock(&self.server1_error) {
                     ui.label("Сервер отключен или недоступен");
                 } else {
-                    ui.label(&*self.server1_data.lock().unwrap());
+                    ui.label(&*lock(&self.server1_view.data));
+                    if let Some(Response::MouseInfo(info)) = &*lock(&self.server1_view.response) {
+                        show_devices_table(ui, &info.devices);
+                    }
+                }
+
+                let events = lock(&self.server1_view.events);
+                if !events.is_empty() {
+                    ui.label("События устройств:");
+                    for event in events.iter().rev() { // Новые события сверху
+                        ui.label(event);
+                    }
                 }
 
                 ui.label("Сервер 2:");
-                if *self.server2_error.lock().unwrap() {
+                if let Some(reason) = &*lock(&self.server2_view.denied) {
+                    ui.colored_label(egui::Color32::RED, format!("Доступ запрещён: {}", reason));
+                } else if *lock(&self.server2_error) {
                     ui.label("Сервер отключен или недоступен");
                 } else {
-                    ui.label(&*self.server2_data.lock().unwrap());
+                    ui.label(&*lock(&self.server2_view.data));
+                }
+
+                egui::CollapsingHeader::new("Сведения о системе").show(ui, |ui| {
+                    if ui.button("Обновить").clicked() {
+                        if let Some(sender) = &self.server2_command_sender {
+                            let _ = sender.send(ServerCommand::HostInfo);
+                        }
+                    }
+                    match &*lock(&self.server2_view.host) {
+                        Some(host) => show_host_info(ui, host),
+                        None => {
+                            ui.label("Нет данных");
+                        }
+                    }
+                });
+
+                let events = lock(&self.server2_view.events);
+                if !events.is_empty() {
+                    ui.label("События сервера:");
+                    for event in events.iter().rev() {
+                        ui.label(event);
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Процесс (PID или имя):");
+                    let field = ui.text_edit_singleline(&mut self.process_query);
+                    let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if (ui.button("Найти").clicked() || submitted) && !self.process_query.trim().is_empty() {
+                        if let Some(sender) = &self.server2_command_sender {
+                            let _ = sender.send(ServerCommand::FindProcess(self.process_query.trim().to_string()));
+                        }
+                    }
+                });
+                match &*lock(&self.server2_view.lookup) {
+                    Some(Response::Processes { processes }) => show_processes_table(ui, processes),
+                    Some(Response::ProcessLookupFailed { reason, message }) => {
+                        ui.label(format!("Ошибка поиска ({}): {}", reason, message));
+                    }
+                    _ => {}
                 }
             });
 
             ui.separator();
 
-            ui.label(format!("Статус: {}", *self.status_message.lock().unwrap()));
+            ui.label(format!("Статус: {}", *lock(&self.status_message)));
         });
+
+        ctx.request_repaint_after(COMMAND_POLL_INTERVAL * 5); // Данные приходят из фоновых потоков
     }
 }
 
+// Доступ к общим данным окна и потоков обмена. Аварийное завершение другого потока
+// не делает данные непригодными (это строки и последние ответы), поэтому блокировка не паникует
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Добавление события в список с ограничением его длины
+fn push_event(events: &Mutex<Vec<String>>, event: String) {
+    let mut events = lock(events);
+    events.push(event);
+    if events.len() > MAX_EVENTS {
+        let excess = events.len() - MAX_EVENTS;
+        events.drain(..excess);
+    }
+}
+
+// Проверка перезапуска сервера 2: сменился идентификатор запуска или PID, либо уменьшилось время работы.
+// Возвращает время нового запуска (секунды UNIX)
+fn detect_restart(previous: Option<&ProcessInfo>, current: &ProcessInfo) -> Option<i64> {
+    let previous = previous?;
+    let restarted = previous.instance_id != current.instance_id
+        || previous.pid != current.pid
+        || current.uptime_ms < previous.uptime_ms;
+    restarted.then(|| current.timestamp - (current.uptime_ms / 1000) as i64)
+}
+
+// Сведения о системе сервера 2
+fn show_host_info(ui: &mut egui::Ui, host: &HostInfo) {
+    let used = host.total_memory_bytes.saturating_sub(host.available_memory_bytes);
+    let rows = [
+        ("Имя узла", host.hostname.clone()),
+        ("ОС", format!("{} {} ({})", host.os, host.kernel_release, host.architecture)),
+        ("Время работы системы", format_duration_ms(host.uptime_ms)),
+        (
+            "Средняя загрузка (1/5/15 мин)",
+            format!("{:.2} / {:.2} / {:.2}", host.load_average[0], host.load_average[1], host.load_average[2]),
+        ),
+        (
+            "Память",
+            format!(
+                "занято {} из {}, доступно {}",
+                format_bytes(used),
+                format_bytes(host.total_memory_bytes),
+                format_bytes(host.available_memory_bytes)
+            ),
+        ),
+        ("Процессоров", host.cpu_count.to_string()),
+        ("Время получения данных", format_timestamp(host.timestamp)),
+    ];
+
+    egui::Grid::new("server2_host").striped(true).show(ui, |ui| {
+        for (name, value) in rows {
+            ui.label(name);
+            ui.label(value);
+            ui.end_row();
+        }
+    });
+}
+
+// Таблица указывающих устройств сервера 1
+fn show_devices_table(ui: &mut egui::Ui, devices: &[PointingDevice]) {
+    if devices.is_empty() {
+        ui.label("Указывающие устройства не обнаружены");
+        return;
+    }
+
+    egui::Grid::new("server1_devices").striped(true).show(ui, |ui| {
+        for header in ["Устройство", "VID:PID", "Шина", "Кнопки", "Колесо", "Гориз. колесо", "Тип"] {
+            ui.strong(header);
+        }
+        ui.end_row();
+
+        let yes_no = |flag: bool| if flag { "да" } else { "нет" };
+        for device in devices {
+            ui.label(&device.name);
+            ui.label(format!("{:04x}:{:04x}", device.vendor_id, device.product_id));
+            ui.label(device.bus.to_string());
+            ui.label(device.buttons.to_string());
+            ui.label(yes_no(device.vertical_wheel));
+            ui.label(yes_no(device.horizontal_wheel));
+            ui.label(device.kind.to_string());
+            ui.end_row();
+        }
+    });
+}
+
+// Таблица найденных процессов сервера 2
+fn show_processes_table(ui: &mut egui::Ui, processes: &[ProcessDetails]) {
+    egui::Grid::new("server2_processes").striped(true).show(ui, |ui| {
+        for header in ["PID", "Команда", "Состояние", "Время работы", "Память", "Вирт. память", "Владелец"] {
+            ui.strong(header);
+        }
+        ui.end_row();
+
+        for process in processes {
+            ui.label(process.pid.to_string());
+            ui.label(&process.command_line);
+            ui.label(&process.state);
+            ui.label(format_duration_ms(process.uptime_ms));
+            ui.label(format_bytes(process.rss_bytes));
+            ui.label(format_bytes(process.virtual_memory_bytes));
+            ui.label(&process.owner);
+            ui.end_row();
+        }
+    });
+}
+
 // Методы для приложения
 impl ClientApp {
     fn disconnect_from_server(&mut self, server_number: u8) { // Отключение серверов
         match server_number {
-            1 => {
-                if self.connected_to_server1 {
-                    if let Some(stop_sender) = self.server1_stop_sender.take() {
-                        let _ = stop_sender.send(());
-                    }
-                    self.connected_to_server1 = false;
-                    *self.server1_error.lock().unwrap() = false;
-                    *self.server1_error_logged.lock().unwrap() = false;
-                    *self.server1_data.lock().unwrap() = "Нет данных".to_string();
-                    *self.status_message.lock().unwrap() = "Отключено от сервера 1".to_string();
-                    self.log_sender.send(format!("Отключено от сервера 1. ID клиента: {}", self.client_id)).unwrap();
+            1 if self.connected_to_server1 => {
+                if let Some(command_sender) = self.server1_command_sender.take() {
+                    let _ = command_sender.send(ServerCommand::Stop);
                 }
+                self.connected_to_server1 = false;
+                *lock(&self.server1_error) = false;
+                *lock(&self.server1_error_logged) = false;
+                self.server1_view.clear();
+                *lock(&self.status_message) = "Отключено от сервера 1".to_string();
+                info!(client_id = self.client_id, server = "сервер 1", "Отключено от сервера");
             }
-            2 => {
-                if self.connected_to_server2 {
-                    if let Some(stop_sender) = self.server2_stop_sender.take() {
-                        let _ = stop_sender.send(());
-                    }
-                    self.connected_to_server2 = false;
-                    *self.server2_error.lock().unwrap() = false;
-                    *self.server2_error_logged.lock().unwrap() = false;
-                    *self.server2_data.lock().unwrap() = "Нет данных".to_string();
-                    *self.status_message.lock().unwrap() = "Отключено от сервера 2".to_string();
-                    self.log_sender.send(format!("Отключено от сервера 2. ID клиента: {}", self.client_id)).unwrap();
+            2 if self.connected_to_server2 => {
+                if let Some(command_sender) = self.server2_command_sender.take() {
+                    let _ = command_sender.send(ServerCommand::Stop);
                 }
+                self.connected_to_server2 = false;
+                *lock(&self.server2_error) = false;
+                *lock(&self.server2_error_logged) = false;
+                self.server2_view.clear();
+                *lock(&self.status_message) = "Отключено от сервера 2".to_string();
+                info!(client_id = self.client_id, server = "сервер 2", "Отключено от сервера");
             }
             _ => {}
         }
     }
 }
 
-// Функции форматирования ответов от серверов из JSON в удобный формат
-fn format_server1_response(json_str: &str) -> String {
-    match serde_json::from_str::<Value>(json_str) {
-        Ok(json) => {
-            let buttons = json["mouse_buttons"].as_i64().unwrap_or(0);
-            let has_wheel = json["has_scroll_wheel"].as_i64().unwrap_or(0) != 0;
-            let timestamp = json["timestamp"].as_i64().unwrap_or(0);
+// Функции форматирования ответов от серверов в удобный формат
+fn format_timestamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "неизвестно".to_string())
+}
+
+// Продолжительность в часах, минутах и секундах
+fn format_duration_ms(duration_ms: u64) -> String {
+    let secs = duration_ms / 1000;
+    format!("{} ч {} мин {} сек", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+// Размер в байтах в удобных единицах
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["Б", "КиБ", "МиБ", "ГиБ"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
 
+fn format_server1_response(response: &Response) -> String {
+    match response {
+        Response::MouseInfo(info) => {
             format!(
-                "Количество кнопок мыши: {}\nНаличие колесика мыши: {}\nВремя получения данных: {}",
-                buttons,
-                if has_wheel { "да" } else { "нет" },
-                DateTime::from_timestamp(timestamp, 0)
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|| "неизвестно".to_string())
+                "Количество кнопок мыши: {}\nНаличие колесика мыши: {}\nУказывающих устройств: {}\nВремя получения данных: {}",
+                info.mouse_buttons,
+                if info.has_scroll_wheel { "да" } else { "нет" },
+                info.devices.len(),
+                format_timestamp(info.timestamp)
             )
         }
-        Err(e) => {
-            format!("Ошибка парсинга данных: {}\nСырой ответ:\n{}", e, json_str)
-        }
+        other => format_unexpected_response(other),
     }
 }
 
-fn format_server2_response(json_str: &str) -> String {
-    match serde_json::from_str::<Value>(json_str) {
-        Ok(json) => {
-            let pid = json["pid"].as_u64().unwrap_or(0);
-            let uptime_ms = json["uptime_ms"].as_u64().unwrap_or(0);
-            let timestamp = json["timestamp"].as_i64().unwrap_or(0);
-
-            let uptime_secs = uptime_ms / 1000;
-            let hours = uptime_secs / 3600;
-            let minutes = (uptime_secs % 3600) / 60;
-            let seconds = uptime_secs % 60;
+fn format_server2_response(response: &Response) -> String {
+    match response {
+        Response::ProcessInfo(info) => {
+            let metrics = match &info.metrics {
+                Some(metrics) => format!(
+                    "Резидентная память: {}\nВиртуальная память: {}\nПроцессорное время: польз. {:.2} с, сист. {:.2} с\n\
+                     Количество потоков: {}\nОткрытых дескрипторов: {}\nВремя запуска процесса: {}\n",
+                    format_bytes(metrics.rss_bytes),
+                    format_bytes(metrics.virtual_memory_bytes),
+                    metrics.user_cpu_ms as f64 / 1000.0,
+                    metrics.system_cpu_ms as f64 / 1000.0,
+                    metrics.threads,
+                    metrics.open_fds,
+                    format_timestamp(metrics.start_time)
+                ),
+                None => "Метрики процесса недоступны\n".to_string(),
+            };
 
             format!(
-                "ID процесса сервера: {}\nВремя работы сервера: {} ч {} мин {} сек\nВремя получения данных: {}",
-                pid,
-                hours,
-                minutes,
-                seconds,
-                DateTime::from_timestamp(timestamp, 0)
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|| "неизвестно".to_string())
+                "ID процесса сервера: {}\nИдентификатор запуска: {}\nВремя работы сервера: {}\n{}Время получения данных: {}",
+                info.pid,
+                info.instance_id,
+                format_duration_ms(info.uptime_ms),
+                metrics,
+                format_timestamp(info.timestamp)
             )
         }
-        Err(e) => {
-            format!("Ошибка парсинга данных: {}\nСырой ответ:\n{}", e, json_str)
+        other => format_unexpected_response(other),
+    }
+}
+
+// Ответ, не соответствующий серверу (ошибка или данные другого сервера)
+fn format_unexpected_response(response: &Response) -> String {
+    match response {
+        Response::Error { message } => format!("Ошибка сервера: {}", message),
+        other => format!("Ошибка: неожиданный ответ сервера: {:?}", other),
+    }
+}
+
+// Обмен приветствиями с сервером; при несовместимости возвращает текст ошибки
+// Возвращается имя, под которым сервер опознал клиента, если сервер проверяет подлинность
+fn handshake(
+    stream: &mut Connection,
+    expected_kind: ServerKind,
+    client_id: u64,
+    token: Option<AuthToken>,
+) -> Result<Option<String>, ClientError> {
+    // Тип сервера указывается, чтобы общий сервер с несколькими источниками выбрал нужный
+    let hello = Request::Hello { version: PROTOCOL_VERSION, client_id, server_kind: Some(expected_kind), token };
+    write_message(stream, &hello).map_err(|e| ClientError::Handshake(format!("ошибка отправки приветствия: {}", e)))?;
+
+    let message = match read_message::<_, Response>(stream) {
+        Ok(Some(Response::Hello { server_kind, version, identity, .. })) => {
+            if version != PROTOCOL_VERSION {
+                format!(
+                    "несовместимая версия протокола: сервер {}, клиент {}",
+                    version, PROTOCOL_VERSION
+                )
+            } else if server_kind != expected_kind {
+                format!(
+                    "неверный тип сервера: ожидался \"{}\", получен \"{}\"",
+                    expected_kind, server_kind
+                )
+            } else {
+                return Ok(identity);
+            }
+        }
+        Ok(Some(Response::Busy { retry_after_ms })) => return Err(ClientError::Busy(retry_after_ms)),
+        Ok(Some(Response::Unauthorized { message })) => return Err(ClientError::Unauthorized(message)),
+        Ok(Some(Response::Rejected { reason, message, retry_after_ms })) => {
+            return Err(ClientError::Rejected(reason, message, retry_after_ms));
+        }
+        Ok(Some(Response::Error { message })) => format!("сервер отклонил подключение: {}", message),
+        Ok(Some(other)) => format!("неожиданный ответ на приветствие: {:?}", other),
+        Ok(None) => "соединение закрыто сервером во время приветствия".to_string(),
+        Err(e) => format!("ошибка чтения приветствия: {}", e),
+    };
+    Err(ClientError::Handshake(message))
+}
+
+// Таймауты и TCP keepalive для соединения с сервером; таймаут чтения ограничивает и приём кадра,
+// и приветствие, поэтому зависший сервер не блокирует поток обмена
+fn configure_stream(stream: TcpStream) -> std::io::Result<TcpStream> {
+    stream.set_read_timeout(Some(PEER_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let keepalive = TcpKeepalive::new().with_time(KEEPALIVE_TIME).with_interval(KEEPALIVE_TIME / 3);
+    SockRef::from(&stream).set_tcp_keepalive(&keepalive)?;
+    Ok(stream)
+}
+
+// Подключение к серверу; при настроенном TLS поверх TCP устанавливается защищённое соединение
+fn connect(address: &str, tls: Option<&TlsSettings>) -> Result<Connection, ClientError> {
+    let stream = TcpStream::connect(address).and_then(configure_stream).map_err(ClientError::Connect)?;
+    let Some(tls) = tls else { return Ok(Connection::Plain(stream)) };
+    // Имя сервера - часть адреса до порта, адрес IPv6 указывается в квадратных скобках
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host).trim_start_matches('[').trim_end_matches(']');
+    let server_name = tls.server_name.as_deref().unwrap_or(host);
+    Connection::tls(stream, server_name, Arc::clone(&tls.config)).map_err(ClientError::Tls)
+}
+
+// Ожидание перед повторным подключением; true - пользователь отключился от сервера
+fn wait_for_retry(commands: &mpsc::Receiver<ServerCommand>, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match commands.recv_timeout(remaining) {
+            Ok(ServerCommand::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => return true,
+            Ok(_) => {} // Запросы до подключения не выполняются
+            Err(mpsc::RecvTimeoutError::Timeout) => return false,
         }
     }
 }
 
-// Асинхронное получение данных от сервера
+// Асинхронное получение данных от сервера по подписке
+#[allow(clippy::too_many_arguments)]
 fn get_server_data_async(
     ip: String,
-    data: Arc<Mutex<String>>,
+    options: ConnectOptions,
+    view: ServerView,
     status: Arc<Mutex<String>>,
-    log_sender: mpsc::Sender<String>,
     server_name: String,
+    server_kind: ServerKind,
     error_flag: Arc<Mutex<bool>>,
     error_logged: Arc<Mutex<bool>>,
-    client_id: u128,
-) -> (thread::JoinHandle<()>, mpsc::Sender<()>) {
-    let (stop_sender, stop_receiver) = mpsc::channel(); // Создание канала для остановки
+    client_id: u64,
+    interval_ms: u64,
+) -> (thread::JoinHandle<()>, mpsc::Sender<ServerCommand>) {
+    let (command_sender, command_receiver) = mpsc::channel(); // Создание канала команд (запрос, подписка, остановка)
 
     let handle = thread::spawn(move || {
-        log_sender.send(format!("Подключение к {}. ID клиента: {}", server_name, client_id)).unwrap();
+        // Записи журнала об обмене с сервером содержат его имя, адрес и идентификатор клиента
+        let _span = tracing::info_span!("server", server = %server_name, peer = %ip, client_id).entered();
+        info!("Подключение к серверу");
 
-        let mut stream = match TcpStream::connect(ip.as_str()) {
-            Ok(stream) => {
-                *error_flag.lock().unwrap() = false;
-                stream
-            }
-            Err(e) => {
-                *error_flag.lock().unwrap() = true;
-                *data.lock().unwrap() = "Ошибка подключения".to_string();
-                if !*error_logged.lock().unwrap() {
-                    log_sender.send(format!("Ошибка подключения к {}. ID клиента: {}. Ошибка: {}", server_name, client_id, e)).unwrap();
-                    *error_logged.lock().unwrap() = true;
+        let mut stream = loop {
+            let mut stream = match connect(&ip, options.tls.as_ref()) {
+                Ok(stream) => {
+                    *lock(&error_flag) = false;
+                    stream
+                }
+                // Сертификат сервера не прошёл проверку или TLS не согласован: повтор не поможет
+                Err(e @ ClientError::Tls(_)) => {
+                    let message = e.to_string();
+                    *lock(&error_flag) = true;
+                    *lock(&view.data) = message.clone();
+                    *lock(&status) = format!("Ошибка подключения к {}: {}", server_name, message);
+                    warn!(error = %message, "Ошибка установки TLS-соединения");
+                    return;
+                }
+                Err(e) => {
+                    *lock(&error_flag) = true;
+                    *lock(&view.data) = "Ошибка подключения".to_string();
+                    if !*lock(&error_logged) {
+                        warn!(error = %e, "Ошибка подключения");
+                        *lock(&error_logged) = true;
+                    }
+                    return;
+                }
+            };
+
+            // Приветствие: проверка версии протокола и типа сервера
+            match handshake(&mut stream, server_kind, client_id, options.token.clone()) {
+                Ok(identity) => {
+                    if let Some(identity) = identity {
+                        info!(identity = %identity, "Сервер опознал клиента");
+                        *lock(&status) = format!("{}: подключено как {}", server_name, identity);
+                    }
+                    break stream;
+                }
+                Err(e @ ClientError::Busy(retry_after_ms)) => {
+                    // Сервер обслуживает максимум клиентов: подключение повторяется после паузы
+                    let message = e.to_string();
+                    *lock(&view.data) = message.clone();
+                    *lock(&status) = format!("{}: {}", server_name, message);
+                    info!(retry_after_ms, "Сервер заполнен, подключение будет повторено");
+                    let _ = stream.shutdown();
+                    if wait_for_retry(&command_receiver, Duration::from_millis(retry_after_ms)) {
+                        return;
+                    }
+                }
+                Err(e @ ClientError::Rejected(reason, _, Some(retry_after_ms))) => {
+                    // Превышена частота подключений: повтор после указанной сервером паузы
+                    let message = e.to_string();
+                    *lock(&view.data) = message.clone();
+                    *lock(&status) = format!("{}: {}", server_name, message);
+                    warn!(%reason, retry_after_ms, "Сервер отказал в обслуживании, подключение будет повторено");
+                    let _ = stream.shutdown();
+                    if wait_for_retry(&command_receiver, Duration::from_millis(retry_after_ms)) {
+                        return;
+                    }
+                }
+                Err(ClientError::Unauthorized(reason) | ClientError::Rejected(_, reason, None)) => {
+                    // Токен, сертификат или адрес клиента не приняты: повтор с теми же данными не поможет
+                    *lock(&error_flag) = true;
+                    *lock(&view.denied) = Some(reason.clone());
+                    *lock(&status) = format!("{} отказал в доступе: {}", server_name, reason);
+                    warn!(reason = %reason, "Сервер отказал в доступе");
+                    let _ = stream.shutdown();
+                    return;
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    *lock(&error_flag) = true;
+                    *lock(&view.data) = message.clone();
+                    *lock(&status) = format!("Ошибка подключения к {}: {}", server_name, message);
+                    warn!(error = %message, "Ошибка приветствия");
+                    let _ = stream.shutdown();
+                    return;
                 }
-                return;
             }
         };
+        info!("Приветствие выполнено");
+
+        // Подписка на рассылку данных сервером
+        if let Err(e) = write_message(&mut stream, &Request::Subscribe { interval_ms }) {
+            *lock(&error_flag) = true;
+            warn!(error = %e, "Ошибка отправки подписки");
+            return;
+        }
+        // Сведения о системе запрашиваются один раз при подключении, далее - по кнопке
+        if server_kind == ServerKind::ProcessInfo {
+            let _ = write_message(&mut stream, &Request::HostInfo);
+        }
+
+        let mut last_sent = Instant::now();     // Время отправки последнего запроса
+        let mut last_received = Instant::now(); // Время получения последнего кадра от сервера
 
         loop {
-            if stop_receiver.try_recv().is_ok() {
-                let _ = stream.shutdown(Shutdown::Both); // Закрытие соединения
-                return;
-            }
+            // Обработка команд интерфейса
+            let request = match command_receiver.try_recv() {
+                Ok(ServerCommand::Get) => Some(Request::Get),
+                Ok(ServerCommand::Subscribe(interval_ms)) => Some(Request::Subscribe { interval_ms }),
+                Ok(ServerCommand::HostInfo) => Some(Request::HostInfo),
+                Ok(ServerCommand::FindProcess(query)) => Some(match query.parse::<u32>() {
+                    Ok(pid) => Request::ProcessInfo { pid },
+                    Err(_) => Request::ProcessFind { name: query },
+                }),
+                Ok(ServerCommand::Stop) | Err(mpsc::TryRecvError::Disconnected) => {
+                    let _ = write_message(&mut stream, &Request::Disconnect); // Уведомляем сервер об отключении
+                    let _ = stream.shutdown(); // Закрытие соединения
+                    return;
+                }
+                Err(mpsc::TryRecvError::Empty) => None,
+            };
+            let request = request.or_else(|| (last_sent.elapsed() >= HEARTBEAT_INTERVAL).then_some(Request::Ping));
 
-            let result = {
-                let request = "request"; // Запрос
-                if let Err(e) = stream.write(request.as_bytes()) {
-                    *error_flag.lock().unwrap() = true;
-                    if !*error_logged.lock().unwrap() {
-                        log_sender.send(format!("Ошибка отправки запроса к {}. ID клиента: {}. Ошибка: {}", server_name, client_id, e)).unwrap();
-                        *error_logged.lock().unwrap() = true;
+            if let Some(request) = request {
+                last_sent = Instant::now();
+                match write_message(&mut stream, &request) {
+                    Ok(bytes) => debug!(bytes, ?request, "Запрос отправлен"),
+                    Err(e) => {
+                        *lock(&error_flag) = true;
+                        if !*lock(&error_logged) {
+                            warn!(error = %e, "Ошибка отправки запроса");
+                            *lock(&error_logged) = true;
+                        }
+                        return;
                     }
-                    continue;
                 }
+            }
 
-                let mut buffer = [0; 1024]; // Буфер чтения данных
-                match stream.read(&mut buffer) {
-                    Ok(len) if len > 0 => {
-                        let json_str = String::from_utf8_lossy(&buffer[..len]).to_string(); // Преобразование данных в строку
-                        if !*error_logged.lock().unwrap() {
-                            log_sender.send(format!("Полученная информация от {}. ID клиента: {}. Данные: {}", server_name, client_id, json_str)).unwrap();
-                        }
+            // Ожидание данных от сервера с периодической проверкой команд
+            match stream.wait_for_frame(COMMAND_POLL_INTERVAL) {
+                Ok(true) => {}
+                Ok(false) if last_received.elapsed() < PEER_TIMEOUT => continue,
+                Ok(false) => {
+                    *lock(&error_flag) = true;
+                    *lock(&view.data) = "Сервер не отвечает".to_string();
+                    warn!(timeout_ms = PEER_TIMEOUT.as_millis() as u64, "Сервер не отвечает, соединение закрыто");
+                    let _ = stream.shutdown();
+                    return;
+                }
+                Err(e) => {
+                    *lock(&error_flag) = true;
+                    warn!(error = %e, "Ошибка чтения информации");
+                    return;
+                }
+            }
 
-                        if server_name == "сервер 1" {
-                            format_server1_response(&json_str)
-                        } else {
-                            format_server2_response(&json_str)
-                        }
-                    }
-                    Ok(_) => {
-                        *error_flag.lock().unwrap() = true;
-                        "Соединение закрыто сервером".to_string()
+            let message = read_message::<_, Response>(&mut stream);
+            if matches!(message, Ok(Some(_))) {
+                last_received = Instant::now();
+            }
+            let (result, connection_lost) = match message {
+                Ok(Some(Response::Pong)) => continue,
+                Ok(Some(Response::ServerShutdown { reason })) => {
+                    // Штатная остановка сервера - не ошибка чтения
+                    info!(reason = %reason, "Сервер завершил работу");
+                    *lock(&view.data) = format!("Сервер завершил работу: {}", reason);
+                    *lock(&status) = format!("{} завершил работу", server_name);
+                    let _ = stream.shutdown();
+                    return;
+                }
+                Ok(Some(Response::Rejected { reason, message, .. })) => {
+                    // Сервер отключает клиента, превысившего частоту запросов
+                    warn!(%reason, message = %message, "Сервер отказал в обслуживании");
+                    *lock(&error_flag) = true;
+                    *lock(&view.data) = format!("Сервер отказал в обслуживании: {}", message);
+                    *lock(&status) = format!("{} отказал в обслуживании: {}", server_name, reason);
+                    let _ = stream.shutdown();
+                    return;
+                }
+                Ok(Some(Response::Subscribed { interval_ms })) => {
+                    info!(interval_ms, "Подписка оформлена");
+                    *lock(&status) = format!("Подписка на {}: каждые {} мс", server_name, interval_ms);
+                    continue;
+                }
+                Ok(Some(Response::Unsubscribed)) => continue,
+                Ok(Some(response @ (Response::Processes { .. } | Response::ProcessLookupFailed { .. }))) => {
+                    debug!(?response, "Результат поиска процессов");
+                    *lock(&view.lookup) = Some(response);
+                    continue;
+                }
+                Ok(Some(Response::HostInfo(host))) => {
+                    debug!(?host, "Сведения о системе");
+                    *lock(&view.host) = Some(host);
+                    continue;
+                }
+                Ok(Some(Response::DeviceAdded { device, timestamp })) => {
+                    info!(device = %device.name, "Подключено устройство");
+                    push_event(&view.events, format!("[{}] Подключено: {}", format_timestamp(timestamp), device.name));
+                    continue;
+                }
+                Ok(Some(Response::DeviceRemoved { device, timestamp })) => {
+                    info!(device = %device.name, "Отключено устройство");
+                    push_event(&view.events, format!("[{}] Отключено: {}", format_timestamp(timestamp), device.name));
+                    continue;
+                }
+                Ok(Some(response)) => {
+                    if !*lock(&error_logged) {
+                        debug!(?response, "Получены данные");
                     }
-                    Err(e) => {
-                        *error_flag.lock().unwrap() = true;
-                        if !*error_logged.lock().unwrap() {
-                            log_sender.send(format!("Ошибка чтения информации от {}. ID клиента: {}. Ошибка: {}", server_name, client_id, e)).unwrap();
-                            *error_logged.lock().unwrap() = true;
+
+                    if let Response::ProcessInfo(info) = &response {
+                        let mut instance = lock(&view.instance);
+                        if let Some(started) = detect_restart(instance.as_ref(), info) {
+                            warn!(started = %format_timestamp(started), "Сервер перезапущен");
+                            push_event(&view.events, format!("Сервер перезапущен в {}", format_timestamp(started)));
                         }
-                        format!("Ошибка чтения: {}", e)
+                        *instance = Some(info.clone());
+                    }
+
+                    let result = match server_kind {
+                        ServerKind::MouseInfo => format_server1_response(&response),
+                        ServerKind::ProcessInfo => format_server2_response(&response),
+                    };
+                    *lock(&view.response) = Some(response);
+                    (result, false)
+                }
+                Ok(None) => {
+                    *lock(&error_flag) = true;
+                    ("Соединение закрыто сервером".to_string(), true)
+                }
+                Err(e) => {
+                    *lock(&error_flag) = true;
+                    if !*lock(&error_logged) {
+                        warn!(error = %e, "Ошибка чтения информации");
+                        *lock(&error_logged) = true;
                     }
+                    (format!("Ошибка чтения: {}", e), true)
                 }
             };
 
-            *data.lock().unwrap() = result.clone();
-            *status.lock().unwrap() = format!(
+            *lock(&view.data) = result.clone();
+            *lock(&status) = format!(
                 "Последнее действие: {}",
                 if result.contains("Ошибка") { "Ошибка" } else { "Успех" }
             );
 
-            thread::sleep(Duration::from_secs(10)); // Ожидание 10 секунд перед следующей отправкой данных
+            if connection_lost {
+                return;
+            }
         }
     });
 
-    (handle, stop_sender)
+    (handle, command_sender)
 }
 
-// Функция логгирования сообщений клиента
-fn logging_client(receiver: mpsc::Receiver<String>) {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("client_log.txt")
-        .unwrap();
-
-    for message in receiver {
-        writeln!(file, "{}", message).unwrap();
+// Значение переменной окружения; None - переменная не задана
+fn env_value<T>(name: &str) -> Result<Option<T>, String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().map(Some).map_err(|e| format!("{}: {}", name, e)),
+        Err(_) => Ok(None),
     }
 }
 
-fn main() -> Result<(), eframe::Error> {
+// Параметры подключения из переменных окружения
+fn connect_options() -> Result<ConnectOptions, String> {
+    let token = std::env::var(TOKEN_ENV).ok().filter(|token| !token.is_empty()).map(AuthToken);
+    Ok(ConnectOptions { tls: tls_settings()?, token })
+}
+
+// Настройки TLS; TLS включается сертификатом CA или отпечатками серверов
+fn tls_settings() -> Result<Option<TlsSettings>, String> {
+    let ca = std::env::var_os(TLS_CA_ENV);
+    let fingerprints = std::env::var(TLS_FINGERPRINT_ENV).ok();
+    let identity = client_identity()?;
+    let trust = match (ca, fingerprints) {
+        (None, None) if identity.is_some() => {
+            return Err(format!("{} требует {} или {}", TLS_CERT_ENV, TLS_CA_ENV, TLS_FINGERPRINT_ENV));
+        }
+        (None, None) => return Ok(None),
+        (Some(_), Some(_)) => return Err(format!("{} и {} заданы одновременно", TLS_CA_ENV, TLS_FINGERPRINT_ENV)),
+        (Some(ca), None) => {
+            ServerTrust::Ca(load_certificates(Path::new(&ca)).map_err(|e| format!("{}: {}", TLS_CA_ENV, e))?)
+        }
+        (None, Some(fingerprints)) => ServerTrust::Pinned(
+            fingerprints
+                .split(',')
+                .map(parse_fingerprint)
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("{}: {}", TLS_FINGERPRINT_ENV, e))?,
+        ),
+    };
+    let config = client_config(&trust, identity).map_err(|e| e.to_string())?;
+    let server_name = std::env::var(TLS_SERVER_NAME_ENV).ok().filter(|name| !name.trim().is_empty());
+    Ok(Some(TlsSettings { config: Arc::new(config), server_name }))
+}
+
+// Сертификат клиента для серверов, опознающих клиентов по сертификату
+fn client_identity() -> Result<Option<ClientIdentity>, String> {
+    let (cert, key) = match (std::env::var_os(TLS_CERT_ENV), std::env::var_os(TLS_KEY_ENV)) {
+        (None, None) => return Ok(None),
+        (Some(cert), Some(key)) => (cert, key),
+        _ => return Err(format!("{} и {} задаются вместе", TLS_CERT_ENV, TLS_KEY_ENV)),
+    };
+    let certificates = load_certificates(Path::new(&cert)).map_err(|e| format!("{}: {}", TLS_CERT_ENV, e))?;
+    let key = load_private_key(Path::new(&key)).map_err(|e| format!("{}: {}", TLS_KEY_ENV, e))?;
+    Ok(Some(ClientIdentity { certificates, key }))
+}
+
+// Журнал клиента; фильтр уровней, формат и ротация задаются переменными окружения
+fn start_logging() -> Result<Logging, String> {
+    let level = std::env::var(LOG_LEVEL_ENV).unwrap_or_else(|_| logging::DEFAULT_FILTER.to_string());
+    logging::parse_filter(&level).map_err(|e| format!("{}: {}", LOG_LEVEL_ENV, e))?;
+    let format = env_value::<LogFormat>(LOG_FORMAT_ENV)?.unwrap_or_default();
+
+    let defaults = Rotation::default();
+    let rotation = Rotation {
+        max_size_bytes: env_value(LOG_MAX_SIZE_ENV)?.unwrap_or(defaults.max_size_bytes),
+        period: env_value(LOG_ROTATION_ENV)?.unwrap_or(defaults.period),
+        keep: env_value(LOG_KEEP_ENV)?.unwrap_or(defaults.keep),
+        compress: env_value(LOG_COMPRESS_ENV)?.unwrap_or(defaults.compress),
+    };
+    // Если файл журнала открыть не удалось, записи выводятся в stderr
+    Logging::init(Path::new(LOG_FILE), format, &level, &rotation).or_else(|e| {
+        eprintln!("Ошибка журнала: {}; записи выводятся в stderr", e);
+        Logging::stderr(format, &level)
+    })
+}
+
+fn main() -> ExitCode {
+    // Без журнала клиент продолжает работу: ошибка выводится в консоль
+    let logging = match start_logging() {
+        Ok(logging) => Some(logging),
+        Err(e) => {
+            eprintln!("Журнал клиента не ведётся: {}", e);
+            None
+        }
+    };
+    let logging = Arc::new(Mutex::new(logging));
+
+    // Некорректные настройки подключения - ошибка запуска: подключаться без шифрования
+    // вместо TLS или без сертификата клиента вместо заданного нельзя
+    let connection = match connect_options() {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!(error = %e, "Ошибка настроек подключения");
+            drop(lock(&logging).take());
+            eprintln!("Ошибка настроек подключения: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     let options = eframe::NativeOptions::default();
-    let app = ClientApp::default();
+    let app = ClientApp::new(connection);
 
-    let log_sender = app.log_sender.clone();
-    ctrlc::set_handler(move || {
-        log_sender.send(format!("Клиент остановлен. ID клиента: {}", app.client_id)).unwrap();
+    let client_id = app.client_id;
+    let handler_logging = Arc::clone(&logging);
+    let handler = ctrlc::set_handler(move || {
+        info!(client_id, "Клиент остановлен");
+        drop(lock(&handler_logging).take()); // Запись накопленных сообщений журнала
         std::process::exit(0);
-    }).expect("Ошибка установки обработчика Ctrl+C");
+    });
+    if let Err(e) = handler {
+        warn!(error = %e, "Не удалось установить обработчик Ctrl+C"); // Окно по-прежнему закрывается кнопкой
+    }
 
-    eframe::run_native(
+    let result = eframe::run_native(
         "Курсовая работа (Вариант 6)", // Заголовок окна
         options,
         Box::new(|_| Ok(Box::new(app))),
     )
+    .map_err(ClientError::Window);
+    if let Err(e) = &result {
+        error!(error = %e, "Клиент завершился с ошибкой");
+    }
+
+    info!(client_id, "Клиент остановлен");
+    drop(lock(&logging).take()); // Запись накопленных сообщений журнала
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Ошибка клиента: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(instance_id: &str, pid: u32, uptime_ms: u64, timestamp: i64) -> ProcessInfo {
+        ProcessInfo { pid, instance_id: instance_id.to_string(), uptime_ms, metrics: None, timestamp }
+    }
+
+    #[test]
+    fn first_reply_is_not_a_restart() {
+        assert_eq!(detect_restart(None, &info("a", 10, 5_000, 1_000)), None);
+    }
+
+    #[test]
+    fn same_instance_is_not_a_restart() {
+        let previous = info("a", 10, 5_000, 1_000);
+        assert_eq!(detect_restart(Some(&previous), &info("a", 10, 15_000, 1_010)), None);
+        // Перевод системных часов назад не влияет: время работы монотонно
+        assert_eq!(detect_restart(Some(&previous), &info("a", 10, 6_000, 900)), None);
+    }
+
+    #[test]
+    fn restart_is_detected_by_instance_pid_or_uptime() {
+        let previous = info("a", 10, 50_000, 1_000);
+        // Время нового запуска - момент ответа минус время работы
+        assert_eq!(detect_restart(Some(&previous), &info("b", 10, 60_000, 1_100)), Some(1_040));
+        assert_eq!(detect_restart(Some(&previous), &info("a", 11, 60_000, 1_100)), Some(1_040));
+        assert_eq!(detect_restart(Some(&previous), &info("a", 10, 2_500, 1_100)), Some(1_098));
+    }
 }
@@ -0,0 +1,115 @@
+// Журналирование серверов и клиента: записи с меткой времени RFC 3339, уровнем,
+// потоком и полями событий в файл, в текстовом виде или построчно в JSON
+mod rotation;
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::time::ChronoLocal;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+pub use rotation::{RotatingFile, Rotation, RotationPeriod};
+
+// Фильтр по умолчанию: сообщения уровня info и выше
+pub const DEFAULT_FILTER: &str = "info";
+
+// Формат записей журнала
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Plain, // Строка текста на запись
+    Json,  // Объект JSON на строку
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "plain" => Ok(LogFormat::Plain),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("неизвестный формат \"{}\" (допустимо: plain, json)", other)),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Plain => write!(f, "plain"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+// Проверка фильтра уровней, например "info" или "warn,server1=debug"
+pub fn parse_filter(filter: &str) -> Result<EnvFilter, String> {
+    EnvFilter::try_new(filter).map_err(|e| format!("некорректный фильтр \"{}\": {}", filter, e))
+}
+
+// Установленный журнал. Записи пишутся в файл отдельным потоком;
+// при удалении значения накопленные записи дописываются в файл
+pub struct Logging {
+    filter: LogFilter,
+    _writer: WorkerGuard,
+}
+
+// Фильтр уровней установленного журнала, изменяемый во время работы
+#[derive(Clone)]
+pub struct LogFilter(reload::Handle<EnvFilter, Registry>);
+
+impl LogFilter {
+    pub fn set(&self, filter: &str) -> Result<(), String> {
+        let filter = parse_filter(filter)?;
+        self.0.reload(filter).map_err(|e| format!("не удалось сменить фильтр журнала: {}", e))
+    }
+}
+
+impl Logging {
+    // Установка журнала для всего процесса; вызывается один раз при запуске
+    pub fn init(path: &Path, format: LogFormat, filter: &str, rotation: &Rotation) -> Result<Self, String> {
+        let filter = parse_filter(filter)?;
+        let file = RotatingFile::open(path, rotation.clone())
+            .map_err(|e| format!("не удалось открыть журнал {}: {}", path.display(), e))?;
+        Logging::install(file, format, filter)
+    }
+
+    // Журнал в stderr, если файл журнала открыть не удалось
+    pub fn stderr(format: LogFormat, filter: &str) -> Result<Self, String> {
+        Logging::install(io::stderr(), format, parse_filter(filter)?)
+    }
+
+    fn install<W: Write + Send + 'static>(output: W, format: LogFormat, filter: EnvFilter) -> Result<Self, String> {
+        let (writer, guard) = tracing_appender::non_blocking(output);
+
+        let (filter, handle) = reload::Layer::new(filter);
+        let output = tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_timer(ChronoLocal::rfc_3339())
+            .with_ansi(false)
+            .with_thread_ids(true)
+            .with_thread_names(true);
+        let output = match format {
+            LogFormat::Plain => output.boxed(),
+            LogFormat::Json => output.json().with_current_span(true).with_span_list(false).boxed(),
+        };
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(output)
+            .try_init()
+            .map_err(|e| format!("журнал уже установлен: {}", e))?;
+        Ok(Logging { filter: LogFilter(handle), _writer: guard })
+    }
+
+    pub fn filter(&self) -> LogFilter {
+        self.filter.clone()
+    }
+}
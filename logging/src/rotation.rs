@@ -0,0 +1,395 @@
+// Ротация файла журнала: по размеру и по смене часа или суток, с хранением
+// заданного числа старых файлов и необязательным сжатием их в gzip
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc;
+use std::thread;
+
+use chrono::{DateTime, Local};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+// Значения по умолчанию
+const DEFAULT_MAX_SIZE_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_KEEP: usize = 7;
+
+// Настройки ротации
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rotation {
+    pub max_size_bytes: u64,    // Размер файла, после которого начинается новый (0 - без ограничения)
+    pub period: RotationPeriod, // Начало нового файла с каждым часом или сутками
+    pub keep: usize,            // Число хранимых старых файлов
+    pub compress: bool,         // Сжатие старых файлов в gzip
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Rotation {
+            max_size_bytes: DEFAULT_MAX_SIZE_BYTES,
+            period: RotationPeriod::Daily,
+            keep: DEFAULT_KEEP,
+            compress: true,
+        }
+    }
+}
+
+// Период ротации по времени (местному)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationPeriod {
+    Never,
+    Hourly,
+    Daily,
+}
+
+impl RotationPeriod {
+    // Обозначение периода, к которому относится момент времени; None - ротации по времени нет
+    fn key(self, time: &DateTime<Local>) -> Option<String> {
+        match self {
+            RotationPeriod::Never => None,
+            RotationPeriod::Hourly => Some(time.format("%Y%m%d%H").to_string()),
+            RotationPeriod::Daily => Some(time.format("%Y%m%d").to_string()),
+        }
+    }
+}
+
+impl FromStr for RotationPeriod {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "never" => Ok(RotationPeriod::Never),
+            "hourly" => Ok(RotationPeriod::Hourly),
+            "daily" => Ok(RotationPeriod::Daily),
+            other => Err(format!("неизвестный период \"{}\" (допустимо: never, hourly, daily)", other)),
+        }
+    }
+}
+
+impl fmt::Display for RotationPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RotationPeriod::Never => write!(f, "never"),
+            RotationPeriod::Hourly => write!(f, "hourly"),
+            RotationPeriod::Daily => write!(f, "daily"),
+        }
+    }
+}
+
+// Файл журнала с ротацией. Старые файлы получают суффикс с временем ротации:
+// server_log.txt.20261018-060212 (или .20261018-060212.gz при сжатии)
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    file: File,
+    size: u64,              // Текущий размер файла
+    period: Option<String>, // Период, к которому относятся записи текущего файла
+    rotated: mpsc::Sender<PathBuf>, // Старые файлы для сжатия и удаления лишних
+    failed: bool,                   // Последняя запись в файл не удалась, записи выводятся в stderr
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, rotation: Rotation) -> io::Result<Self> {
+        let file = open_append(path)?;
+        let metadata = file.metadata()?;
+        // Файл, оставшийся от прошлого запуска, относится к периоду своего последнего изменения
+        let modified = match metadata.modified() {
+            Ok(modified) if metadata.len() > 0 => DateTime::<Local>::from(modified),
+            _ => Local::now(),
+        };
+        // Сжатие и удаление старых файлов выполняются по очереди отдельным потоком,
+        // чтобы не задерживать запись журнала
+        // Устройство или канал (/dev/null, /dev/stderr) не переименовываются: ротации нет
+        let rotation = if metadata.is_file() {
+            rotation
+        } else {
+            Rotation { max_size_bytes: 0, period: RotationPeriod::Never, ..rotation }
+        };
+        let (rotated, pending) = mpsc::channel();
+        let (log_path, keep, compress) = (path.to_path_buf(), rotation.keep, rotation.compress);
+        thread::Builder::new()
+            .name("log-rotation".to_string())
+            .spawn(move || maintain(&log_path, keep, compress, pending))?;
+
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            period: rotation.period.key(&modified),
+            rotation,
+            file,
+            size: metadata.len(),
+            rotated,
+            failed: false,
+        })
+    }
+
+    fn needs_rotation(&self, incoming: usize, now: &DateTime<Local>) -> bool {
+        let max_size = self.rotation.max_size_bytes;
+        let too_large = max_size > 0 && self.size > 0 && self.size + incoming as u64 > max_size;
+        too_large || self.rotation.period.key(now) != self.period
+    }
+
+    fn rotate(&mut self, now: &DateTime<Local>) -> io::Result<()> {
+        // При ошибке следующая попытка будет только в следующем периоде или после нового заполнения
+        self.size = 0;
+        self.period = self.rotation.period.key(now);
+
+        self.file.flush()?;
+        let rotated = rotated_path(&self.path, now);
+        fs::rename(&self.path, &rotated)?;
+        self.file = open_append(&self.path)?;
+        let _ = self.rotated.send(rotated);
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let now = Local::now();
+        if self.needs_rotation(buf.len(), &now) {
+            // Запись продолжается в текущий файл, даже если ротация не удалась
+            if let Err(e) = self.rotate(&now) {
+                eprintln!("Не удалось выполнить ротацию журнала {}: {}", self.path.display(), e);
+            }
+        }
+        match self.file.write(buf) {
+            Ok(written) => {
+                if self.failed {
+                    self.failed = false;
+                    eprintln!("Запись журнала {} возобновлена", self.path.display());
+                }
+                self.size += written as u64;
+                Ok(written)
+            }
+            // Файл недоступен (например, диск заполнен): записи не теряются, а выводятся в stderr
+            Err(e) => {
+                if !self.failed {
+                    self.failed = true;
+                    eprintln!("Ошибка записи журнала {}: {}; записи выводятся в stderr", self.path.display(), e);
+                }
+                io::stderr().write_all(buf)?;
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+// Имя для старого файла; при нескольких ротациях в одну секунду добавляется номер
+fn rotated_path(path: &Path, now: &DateTime<Local>) -> PathBuf {
+    let base = format!("{}.{}", path.display(), now.format("%Y%m%d-%H%M%S"));
+    let mut candidate = PathBuf::from(&base);
+    let mut number = 1;
+    while candidate.exists() || Path::new(&format!("{}.gz", candidate.display())).exists() {
+        candidate = PathBuf::from(format!("{}-{:03}", base, number));
+        number += 1;
+    }
+    candidate
+}
+
+// Обработка старых файлов до закрытия журнала
+fn maintain(path: &Path, keep: usize, compress: bool, pending: mpsc::Receiver<PathBuf>) {
+    for rotated in pending {
+        match compress.then(|| compress_file(&rotated)) {
+            // Файл мог быть уже удалён как лишний при обработке предыдущих
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => {
+                eprintln!("Не удалось сжать журнал {}: {}", rotated.display(), e);
+            }
+            _ => {}
+        }
+        remove_old(path, keep);
+    }
+}
+
+// Сжатие старого файла в .gz с удалением исходного
+fn compress_file(path: &Path) -> io::Result<()> {
+    let compressed = PathBuf::from(format!("{}.gz", path.display()));
+    let mut source = File::open(path)?;
+    let mut encoder = GzEncoder::new(File::create(&compressed)?, Compression::default());
+    io::copy(&mut source, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)
+}
+
+// Удаление старых файлов сверх заданного числа, начиная с самых ранних
+fn remove_old(path: &Path, keep: usize) {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else { return };
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = fs::read_dir(directory) else { return };
+
+    // Старые файлы: имя журнала, точка и время ротации
+    let prefix = format!("{}.", name);
+    let mut rotated: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_name().to_str().and_then(|file| file.strip_prefix(&prefix)).is_some_and(|suffix| {
+                suffix.starts_with(|c: char| c.is_ascii_digit())
+            })
+        })
+        .map(|entry| entry.path())
+        .collect();
+    // Порядок по времени ротации независимо от сжатия
+    rotated.sort_by_key(|old| old.to_string_lossy().trim_end_matches(".gz").to_string());
+
+    let excess = rotated.len().saturating_sub(keep);
+    for old in &rotated[..excess] {
+        if let Err(e) = fs::remove_file(old) {
+            eprintln!("Не удалось удалить старый журнал {}: {}", old.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::time::Duration;
+
+    use chrono::{TimeDelta, TimeZone};
+    use flate2::read::GzDecoder;
+    use test_support::TestDir;
+
+    use super::*;
+
+    fn time(hour: u32, second: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 18, hour, 2, second).unwrap()
+    }
+
+    // Старые файлы журнала в каталоге по возрастанию имени
+    fn rotated_files(directory: &TestDir) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name != "log.txt")
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn rotation(max_size_bytes: u64, period: RotationPeriod, keep: usize, compress: bool) -> Rotation {
+        Rotation { max_size_bytes, period, keep, compress }
+    }
+
+    #[test]
+    fn file_is_rotated_when_size_limit_is_reached() {
+        let directory = TestDir::new("logging_size");
+        let path = directory.path().join("log.txt");
+        let mut file = RotatingFile::open(&path, rotation(100, RotationPeriod::Never, 10, false)).unwrap();
+
+        file.write_all(&[b'a'; 60]).unwrap();
+        assert!(rotated_files(&directory).is_empty());
+        file.write_all(&[b'b'; 60]).unwrap(); // 120 байт не помещаются в 100
+        file.write_all(&[b'c'; 60]).unwrap();
+        // Запись больше предела целиком попадает в новый файл
+        file.write_all(&[b'd'; 200]).unwrap();
+
+        assert_eq!(rotated_files(&directory).len(), 3, "{:?}", rotated_files(&directory));
+        assert_eq!(fs::read(&path).unwrap(), vec![b'd'; 200]);
+    }
+
+    #[test]
+    fn period_change_starts_a_new_file() {
+        let directory = TestDir::new("logging_period");
+        let path = directory.path().join("log.txt");
+        let mut file = RotatingFile::open(&path, rotation(0, RotationPeriod::Hourly, 10, false)).unwrap();
+        let now = Local::now();
+        assert!(!file.needs_rotation(1 << 30, &now), "без предела размера ротация только по времени");
+        assert!(file.needs_rotation(1, &(now + TimeDelta::hours(1))));
+
+        let next_hour = time(7, 0);
+        file.rotate(&next_hour).unwrap();
+        assert_eq!(rotated_files(&directory), ["log.txt.20261018-070200"]);
+        assert!(!file.needs_rotation(1, &(next_hour + TimeDelta::minutes(30))));
+        assert!(RotationPeriod::Never.key(&now).is_none());
+        assert_ne!(RotationPeriod::Daily.key(&time(0, 0)), RotationPeriod::Daily.key(&(time(0, 0) - TimeDelta::hours(1))));
+    }
+
+    #[test]
+    fn rotated_names_get_a_number_within_one_second() {
+        let directory = TestDir::new("logging_names");
+        let path = directory.path().join("log.txt");
+        let now = time(6, 12);
+
+        let first = rotated_path(&path, &now);
+        assert_eq!(first, directory.path().join("log.txt.20261018-060212"));
+        fs::write(&first, "").unwrap();
+        let second = rotated_path(&path, &now);
+        assert_eq!(second, directory.path().join("log.txt.20261018-060212-001"));
+        // Сжатый файл тоже занимает имя
+        fs::write(format!("{}.gz", second.display()), "").unwrap();
+        assert_eq!(rotated_path(&path, &now), directory.path().join("log.txt.20261018-060212-002"));
+    }
+
+    #[test]
+    fn oldest_files_are_removed_regardless_of_compression() {
+        let directory = TestDir::new("logging_retention");
+        for name in [
+            "log.txt.20261017-235959.gz",
+            "log.txt.20261018-060212.gz",
+            "log.txt.20261018-060212-001",
+            "log.txt.20261018-060213.gz",
+            "log.txt.bak",       // Не старый журнал: после точки не время
+            "other.txt.20261016-000000",
+        ] {
+            directory.file(name, "");
+        }
+
+        remove_old(&directory.path().join("log.txt"), 2);
+        assert_eq!(
+            rotated_files(&directory),
+            ["log.txt.20261018-060212-001", "log.txt.20261018-060213.gz", "log.txt.bak", "other.txt.20261016-000000"]
+        );
+    }
+
+    #[test]
+    fn compressed_file_replaces_original() {
+        let directory = TestDir::new("logging_gzip");
+        let contents = "2026-10-18T06:02:12 INFO Сервер запущен\n".repeat(50);
+        let path = directory.file("log.txt.20261018-060212", &contents);
+
+        compress_file(&path).unwrap();
+        assert!(!path.exists());
+        let mut unpacked = String::new();
+        GzDecoder::new(File::open(directory.path().join("log.txt.20261018-060212.gz")).unwrap())
+            .read_to_string(&mut unpacked)
+            .unwrap();
+        assert_eq!(unpacked, contents);
+    }
+
+    #[test]
+    fn rotated_files_are_compressed_and_pruned_in_background() {
+        let directory = TestDir::new("logging_maintain");
+        let path = directory.path().join("log.txt");
+        let mut file = RotatingFile::open(&path, rotation(100, RotationPeriod::Never, 2, true)).unwrap();
+        for _ in 0..5 {
+            file.write_all(&[b'x'; 80]).unwrap();
+        }
+        drop(file);
+
+        // Сжатие выполняется отдельным потоком: ждём, пока останутся только сжатые старые файлы
+        let mut rotated = Vec::new();
+        for _ in 0..50 {
+            rotated = rotated_files(&directory);
+            if rotated.len() == 2 && rotated.iter().all(|name| name.ends_with(".gz")) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(rotated.len(), 2, "{:?}", rotated);
+        assert!(rotated.iter().all(|name| name.starts_with("log.txt.") && name.ends_with(".gz")), "{:?}", rotated);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 80);
+    }
+}
@@ -0,0 +1,148 @@
+// Настройки общего сервера: таблица [services] (какие источники запущены и на каких адресах)
+// и таблица [mouse] источника сведений о мыши. Общие настройки сервера задаются в server-core
+use std::net::{Ipv4Addr, SocketAddr};
+
+use clap::{Args, ValueEnum};
+use serde::de::{self, MapAccess};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize};
+use server1::{MouseArgs, MouseConfig};
+use server_core::ProviderSettings;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiConfig {
+    pub services: ServicesConfig,
+    pub mouse: MouseConfig,
+}
+
+// Размещение источников. В режиме separate адреса network.listen не используются
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServicesConfig {
+    pub mode: ServiceMode,
+    pub sources: Vec<Source>,            // Запускаемые источники; в режиме shared первый - для клиентов без типа
+    pub mouse_listen: Vec<SocketAddr>,   // Адреса источника сведений о мыши в режиме separate
+    pub process_listen: Vec<SocketAddr>, // Адреса источника сведений о процессах в режиме separate
+}
+
+impl Default for ServicesConfig {
+    fn default() -> Self {
+        ServicesConfig {
+            mode: ServiceMode::Separate,
+            sources: vec![Source::Mouse, Source::Process],
+            // Те же порты, что у серверов 1 и 2: клиенту всё равно, какой процесс ему отвечает
+            mouse_listen: vec![SocketAddr::from((Ipv4Addr::UNSPECIFIED, server1::SPEC.default_port))],
+            process_listen: vec![SocketAddr::from((Ipv4Addr::UNSPECIFIED, server2::SPEC.default_port))],
+        }
+    }
+}
+
+impl ServicesConfig {
+    // Адреса источника в режиме separate
+    pub fn listen(&self, source: Source) -> &[SocketAddr] {
+        match source {
+            Source::Mouse => &self.mouse_listen,
+            Source::Process => &self.process_listen,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceMode {
+    #[default]
+    Separate, // Каждый источник на своих адресах
+    Shared,   // Все источники на адресах network.listen, выбор по типу сервера в приветствии
+}
+
+// Источник данных общего сервера
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Mouse,   // Сведения о мыши (сервер 1)
+    Process, // Сведения о процессах и системе (сервер 2)
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Source::Mouse => "mouse",
+            Source::Process => "process",
+        }
+    }
+}
+
+// Ключи командной строки (переменные окружения MULTISERVER_MODE, MULTISERVER_SOURCES и т.д.)
+#[derive(Debug, Clone, Args)]
+pub struct MultiArgs {
+    #[arg(long, value_name = "MODE",
+          help = "Размещение источников: separate - каждый на своих адресах, shared - все на адресах --listen")]
+    mode: Option<ServiceMode>,
+
+    #[arg(long, value_name = "SOURCE", value_delimiter = ',', help = "Запускаемые источники через запятую")]
+    sources: Vec<Source>,
+
+    #[arg(long, value_name = "ADDR", value_delimiter = ',', help = "Адреса источника сведений о мыши в режиме separate")]
+    mouse_listen: Vec<SocketAddr>,
+
+    #[arg(long, value_name = "ADDR", value_delimiter = ',',
+          help = "Адреса источника сведений о процессах в режиме separate")]
+    process_listen: Vec<SocketAddr>,
+
+    #[command(flatten)]
+    mouse: MouseArgs,
+}
+
+impl ProviderSettings for MultiConfig {
+    const SECTIONS: &'static [&'static str] = &["services", "mouse"];
+    type Args = MultiArgs;
+
+    fn apply(&mut self, args: &MultiArgs) {
+        if let Some(mode) = args.mode {
+            self.services.mode = mode;
+        }
+        if !args.sources.is_empty() {
+            self.services.sources = args.sources.clone();
+        }
+        if !args.mouse_listen.is_empty() {
+            self.services.mouse_listen = args.mouse_listen.clone();
+        }
+        if !args.process_listen.is_empty() {
+            self.services.process_listen = args.process_listen.clone();
+        }
+        self.mouse.apply(&args.mouse);
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let sources = &self.services.sources;
+        if sources.is_empty() {
+            return Err("services.sources: не выбран ни один источник".to_string());
+        }
+        for (index, source) in sources.iter().enumerate() {
+            if sources[..index].contains(source) {
+                return Err(format!("services.sources: источник `{}` указан дважды", source.name()));
+            }
+            if self.services.mode == ServiceMode::Separate && self.services.listen(*source).is_empty() {
+                return Err(format!("services.{}_listen: не задан ни один адрес", source.name()));
+            }
+        }
+        if sources.contains(&Source::Mouse) {
+            self.mouse.validate()?;
+        }
+        Ok(())
+    }
+
+    fn read_section<'de, M: MapAccess<'de>>(&mut self, name: &str, map: &mut M) -> Result<(), M::Error> {
+        match name {
+            "services" => self.services = map.next_value()?,
+            "mouse" => self.mouse = map.next_value()?,
+            name => return Err(de::Error::custom(format!("неизвестная таблица `{}`", name))),
+        }
+        Ok(())
+    }
+
+    fn write_sections<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("services", &self.services)?;
+        map.serialize_entry("mouse", &self.mouse)
+    }
+}
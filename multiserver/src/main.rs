@@ -0,0 +1,45 @@
+// Общий сервер: источники серверов 1 и 2 в одном процессе,
+// каждый на своих адресах или все на общих адресах с выбором по типу сервера в приветствии
+mod config;
+
+use config::{MultiConfig, ServiceMode, Source};
+
+use std::process::ExitCode;
+use server_core::{ServerSpec, Service};
+
+static SPEC: ServerSpec = ServerSpec {
+    name: "multiserver",
+    title: "Общий сервер",
+    about: "Общий сервер: сведения о мыши, процессах и системе в одном процессе",
+    version: env!("CARGO_PKG_VERSION"),
+    env_prefix: "MULTISERVER",
+    default_port: 7880,
+    default_log_file: "multiserver_log.txt",
+};
+
+fn main() -> ExitCode {
+    server_core::run_services(&SPEC, |config: &MultiConfig| {
+        let mut providers = Vec::new();
+        for &source in &config.services.sources {
+            let provider = match source {
+                Source::Mouse => server1::mouse_data(&config.mouse)?,
+                Source::Process => server2::process_data(),
+            };
+            providers.push((source, provider));
+        }
+
+        Ok(match config.services.mode {
+            ServiceMode::Shared => vec![Service {
+                providers: providers.into_iter().map(|(_, provider)| provider).collect(),
+                listen: None,
+            }],
+            ServiceMode::Separate => providers
+                .into_iter()
+                .map(|(source, provider)| Service {
+                    providers: vec![provider],
+                    listen: Some(config.services.listen(source).to_vec()),
+                })
+                .collect(),
+        })
+    })
+}
@@ -0,0 +1,82 @@
+// Интеграционная проверка общего сервера: источники на отдельных адресах и на общем адресе
+use std::net::TcpStream;
+use std::process::Command;
+
+use protocol::{write_message, Request, Response, ServerKind, PROTOCOL_VERSION};
+use test_support::{connect, request, ServerProcess};
+
+// Порты, не пересекающиеся с портами серверов 1 и 2 в их тестах
+const SHARED_ADDR: &str = "127.0.0.1:17880";
+const MOUSE_ADDR: &str = "127.0.0.1:17878";
+const PROCESS_ADDR: &str = "127.0.0.1:17879";
+
+fn start_server(mode: &str) -> ServerProcess {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_multiserver"));
+    command
+        .env("MULTISERVER_MODE", mode)
+        .env("MULTISERVER_LISTEN", SHARED_ADDR)
+        .env("MULTISERVER_MOUSE_LISTEN", MOUSE_ADDR)
+        .env("MULTISERVER_PROCESS_LISTEN", PROCESS_ADDR)
+        .env("MULTISERVER_MOUSE_PROVIDER", "fake")
+        .env("MULTISERVER_FAKE_MOUSE", "3:1");
+    ServerProcess::spawn(command)
+}
+
+fn hello(stream: &mut TcpStream, server_kind: Option<ServerKind>) -> Response {
+    request(stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 1, server_kind, token: None })
+}
+
+#[test]
+fn shared_address_selects_provider_by_kind() {
+    let server = start_server("shared");
+
+    // Без типа сервера клиент получает первый источник, запросы остальных источников тоже обслуживаются
+    let mut stream = connect(SHARED_ADDR);
+    match hello(&mut stream, None) {
+        Response::Hello { server_kind, capabilities, .. } => {
+            assert_eq!(server_kind, ServerKind::MouseInfo);
+            for capability in ["get", "subscribe", "hotplug", "process_lookup", "host_info"] {
+                assert!(capabilities.iter().any(|c| c == capability), "{:?}", capabilities);
+            }
+        }
+        other => panic!("Ожидалось приветствие, получено: {:?}", other),
+    }
+    assert!(matches!(request(&mut stream, &Request::Get), Response::MouseInfo(_)));
+    // Поиск процессов обслуживает источник сервера 2 и находит сам запущенный сервер
+    match request(&mut stream, &Request::ProcessFind { name: "multiserver".to_string() }) {
+        Response::Processes { processes } => {
+            assert!(processes.iter().any(|process| process.pid == server.id()), "{:?}", processes);
+        }
+        other => panic!("Ожидался список процессов, получено: {:?}", other),
+    }
+    write_message(&mut stream, &Request::Disconnect).unwrap();
+
+    let mut stream = connect(SHARED_ADDR);
+    assert!(matches!(hello(&mut stream, Some(ServerKind::ProcessInfo)), Response::Hello { server_kind: ServerKind::ProcessInfo, .. }));
+    assert!(matches!(request(&mut stream, &Request::Get), Response::ProcessInfo(_)));
+    write_message(&mut stream, &Request::Disconnect).unwrap();
+}
+
+#[test]
+fn separate_addresses_serve_one_provider_each() {
+    let _server = start_server("separate");
+
+    let mut mouse = connect(MOUSE_ADDR);
+    assert!(matches!(hello(&mut mouse, Some(ServerKind::MouseInfo)), Response::Hello { server_kind: ServerKind::MouseInfo, .. }));
+    assert!(matches!(request(&mut mouse, &Request::Get), Response::MouseInfo(_)));
+    assert!(matches!(request(&mut mouse, &Request::HostInfo), Response::Error { .. }));
+    write_message(&mut mouse, &Request::Disconnect).unwrap();
+
+    let mut process = connect(PROCESS_ADDR);
+    assert!(matches!(hello(&mut process, None), Response::Hello { server_kind: ServerKind::ProcessInfo, .. }));
+    assert!(matches!(request(&mut process, &Request::Get), Response::ProcessInfo(_)));
+    write_message(&mut process, &Request::Disconnect).unwrap();
+
+    // Источника другого типа на адресе нет: приветствие отклоняется
+    let mut wrong = connect(PROCESS_ADDR);
+    match hello(&mut wrong, Some(ServerKind::MouseInfo)) {
+        Response::Error { message } => assert!(message.contains("недоступен"), "{}", message),
+        other => panic!("Ожидалась ошибка, получено: {:?}", other),
+    }
+    assert!(TcpStream::connect(SHARED_ADDR).is_err(), "в режиме separate общий адрес не слушается");
+}
@@ -0,0 +1,264 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Кадр: 4 байта длины (big-endian) + JSON-тело указанной длины
+pub const FRAME_HEADER_SIZE: usize = 4;
+pub const MAX_FRAME_SIZE: usize = 64 * 1024; // Максимальный размер тела кадра (64 КиБ)
+
+// Ошибки чтения и записи кадров
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),                  // Ошибка ввода-вывода (в т.ч. обрыв посреди кадра)
+    TooLarge(usize),                // Длина кадра превышает MAX_FRAME_SIZE
+    Decode(serde_json::Error),      // Тело кадра не является корректным сообщением
+    Encode(serde_json::Error),      // Сообщение не удалось сериализовать
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => write!(f, "ошибка ввода-вывода: {}", e),
+            FrameError::TooLarge(len) => write!(
+                f,
+                "размер кадра {} байт превышает максимум {} байт",
+                len, MAX_FRAME_SIZE
+            ),
+            FrameError::Decode(e) => write!(f, "некорректное сообщение: {}", e),
+            FrameError::Encode(e) => write!(f, "ошибка сериализации сообщения: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        FrameError::Io(e)
+    }
+}
+
+// Кадр целиком: заголовок и тело сообщения
+fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>, FrameError> {
+    let body = serde_json::to_vec(message).map_err(FrameError::Encode)?;
+    if body.len() > MAX_FRAME_SIZE {
+        return Err(FrameError::TooLarge(body.len()));
+    }
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_SIZE + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&body);
+    Ok(frame)
+}
+
+// Размер тела кадра с сообщением: позволяет заранее подогнать ответ под MAX_FRAME_SIZE
+pub fn message_size<T: Serialize>(message: &T) -> Result<usize, FrameError> {
+    serde_json::to_vec(message).map(|body| body.len()).map_err(FrameError::Encode)
+}
+
+// Длина тела из заголовка с проверкой ограничения
+fn body_len(header: [u8; FRAME_HEADER_SIZE]) -> Result<usize, FrameError> {
+    let len = u32::from_be_bytes(header) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(FrameError::TooLarge(len));
+    }
+    Ok(len)
+}
+
+// Сериализация сообщения и запись одного кадра; возвращается размер кадра в байтах
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> Result<usize, FrameError> {
+    let frame = encode(message)?;
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(frame.len())
+}
+
+// Чтение одного кадра; Ok(None) - соединение закрыто между кадрами
+pub fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>, FrameError> {
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    let mut filled = 0;
+    while filled < FRAME_HEADER_SIZE {
+        match reader.read(&mut header[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let mut body = vec![0u8; body_len(header)?];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(FrameError::Decode)
+}
+
+// Ожидание начала очередного кадра не дольше timeout; Ok(false) - истёк таймаут.
+// Данные только просматриваются (peek), после чего восстанавливается прежний таймаут чтения,
+// с которым кадр затем читается целиком
+pub fn wait_for_frame(stream: &TcpStream, timeout: Duration) -> io::Result<bool> {
+    let previous = stream.read_timeout()?;
+    stream.set_read_timeout(Some(timeout.max(Duration::from_millis(1))))?;
+    let result = match stream.peek(&mut [0u8; 1]) {
+        Ok(_) => Ok(true), // Пришли данные или соединение закрыто - разберёт read_message
+        Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(false),
+        Err(e) => Err(e),
+    };
+    stream.set_read_timeout(previous)?;
+    result
+}
+
+// Асинхронная запись одного кадра; возвращается размер кадра в байтах
+#[cfg(feature = "tokio")]
+pub async fn write_message_async<W, T>(writer: &mut W, message: &T) -> Result<usize, FrameError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let frame = encode(message)?;
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(frame.len())
+}
+
+// Асинхронное чтение одного кадра; Ok(None) - соединение закрыто между кадрами.
+// Прерывание посреди кадра теряет прочитанные байты, поэтому вызов не стоит помещать в select!
+#[cfg(feature = "tokio")]
+pub async fn read_message_async<R, T>(reader: &mut R) -> Result<Option<T>, FrameError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    let mut filled = 0;
+    while filled < FRAME_HEADER_SIZE {
+        match reader.read(&mut header[filled..]).await? {
+            0 if filled == 0 => return Ok(None),
+            0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            n => filled += n,
+        }
+    }
+
+    let mut body = vec![0u8; body_len(header)?];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body).map(Some).map_err(FrameError::Decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{Request, Response};
+
+    // Источник, отдающий данные порциями не больше chunk байт, как сокет при медленной сети
+    struct Chunked {
+        data: Vec<u8>,
+        position: usize,
+        chunk: usize,
+    }
+
+    impl Read for Chunked {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.chunk.min(buf.len()).min(self.data.len() - self.position);
+            buf[..len].copy_from_slice(&self.data[self.position..self.position + len]);
+            self.position += len;
+            Ok(len)
+        }
+    }
+
+    fn frame(message: &impl Serialize) -> Vec<u8> {
+        encode(message).unwrap()
+    }
+
+    #[test]
+    fn frame_split_across_reads_is_assembled() {
+        let request = Request::ProcessFind { name: "server2".to_string() };
+        for chunk in [1, 3, 5] {
+            let mut reader = Chunked { data: frame(&request), position: 0, chunk };
+            assert_eq!(read_message::<_, Request>(&mut reader).unwrap(), Some(request.clone()));
+            assert_eq!(read_message::<_, Request>(&mut reader).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn two_frames_in_one_read_are_separated() {
+        let mut data = frame(&Request::Ping);
+        data.extend(frame(&Request::Subscribe { interval_ms: 250 }));
+        let mut reader = Cursor::new(data);
+        assert_eq!(read_message::<_, Request>(&mut reader).unwrap(), Some(Request::Ping));
+        assert_eq!(read_message::<_, Request>(&mut reader).unwrap(), Some(Request::Subscribe { interval_ms: 250 }));
+        assert_eq!(read_message::<_, Request>(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn oversized_length_header_is_rejected() {
+        let mut data = ((MAX_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(b"{}");
+        let result = read_message::<_, Request>(&mut Cursor::new(data));
+        assert!(matches!(result, Err(FrameError::TooLarge(len)) if len == MAX_FRAME_SIZE + 1), "{:?}", result);
+    }
+
+    #[test]
+    fn oversized_message_is_not_encoded() {
+        let message = Response::Error { message: "x".repeat(MAX_FRAME_SIZE) };
+        let result = write_message(&mut Vec::new(), &message);
+        assert!(matches!(result, Err(FrameError::TooLarge(len)) if len > MAX_FRAME_SIZE), "{:?}", result);
+        // Размер, по которому ответ подгоняется заранее, совпадает с размером тела кадра
+        assert_eq!(message_size(&message).unwrap(), MAX_FRAME_SIZE + r#"{"type":"error","message":""}"#.len());
+        assert_eq!(message_size(&Request::Get).unwrap() + FRAME_HEADER_SIZE, frame(&Request::Get).len());
+    }
+
+    #[test]
+    fn eof_inside_frame_is_an_error() {
+        let data = frame(&Request::Get);
+        // Обрыв в заголовке и в теле кадра - ошибка, а не штатное закрытие соединения
+        for len in [2, data.len() - 1] {
+            let result = read_message::<_, Request>(&mut Cursor::new(&data[..len]));
+            assert!(matches!(&result, Err(FrameError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof), "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn invalid_body_is_a_decode_error() {
+        let mut data = 3u32.to_be_bytes().to_vec();
+        data.extend_from_slice(b"{x}");
+        assert!(matches!(read_message::<_, Request>(&mut Cursor::new(data)), Err(FrameError::Decode(_))));
+    }
+
+    #[test]
+    fn unserializable_message_is_an_encode_error() {
+        // Ключи объекта JSON - только строки
+        let message = BTreeMap::from([(vec![1u8], 1)]);
+        assert!(matches!(write_message(&mut Vec::new(), &message), Err(FrameError::Encode(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_frame_split_across_writes_is_assembled() {
+        use tokio::io::AsyncWriteExt;
+
+        let (mut client, mut server) = tokio::io::duplex(8);
+        let mut data = frame(&Request::Hello { version: 1, client_id: 7, server_kind: None, token: None });
+        data.extend(frame(&Request::Disconnect));
+        tokio::spawn(async move {
+            for chunk in data.chunks(3) {
+                client.write_all(chunk).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+
+        let hello = read_message_async::<_, Request>(&mut server).await.unwrap();
+        assert!(matches!(hello, Some(Request::Hello { client_id: 7, .. })), "{:?}", hello);
+        assert_eq!(read_message_async::<_, Request>(&mut server).await.unwrap(), Some(Request::Disconnect));
+        assert_eq!(read_message_async::<_, Request>(&mut server).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+
+mod codec;
+#[cfg(feature = "tls")]
+pub mod tls;
+
+pub use codec::{message_size, read_message, wait_for_frame, write_message, FrameError, FRAME_HEADER_SIZE, MAX_FRAME_SIZE};
+#[cfg(feature = "tokio")]
+pub use codec::{read_message_async, write_message_async};
+
+// Версия протокола; сервер отклоняет клиентов с другой версией
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// Запросы клиента к серверу
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Hello {                                 // Приветствие, первый кадр соединения
+        version: u32,
+        client_id: u64,
+        // Нужный клиенту тип сервера, если на одном адресе их несколько; None - тип по умолчанию
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        server_kind: Option<ServerKind>,
+        // Токен клиента, если сервер проверяет подлинность клиентов
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<AuthToken>,
+    },
+    Get,                                    // Разовый запрос текущих данных сервера
+    Subscribe { interval_ms: u64 },         // Подписка на рассылку данных с заданным интервалом
+    Unsubscribe,                            // Отмена подписки
+    ProcessInfo { pid: u32 },               // Сервер 2: сведения о процессе по PID
+    ProcessFind { name: String },           // Сервер 2: поиск процессов по имени
+    HostInfo,                               // Сервер 2: сведения о системе
+    Ping,                                   // Проверка связи; клиент отправляет периодически
+    Disconnect,                             // Запрос на отключение
+}
+
+// Ответы сервера клиенту
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Hello {                        // Ответ на приветствие
+        server_kind: ServerKind,
+        version: u32,
+        capabilities: Vec<String>, // Поддерживаемые сервером запросы
+        // Имя, под которым сервер опознал клиента; None - сервер не проверяет подлинность
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        identity: Option<String>,
+    },
+    Subscribed { interval_ms: u64 }, // Подписка оформлена (интервал с учётом ограничений сервера)
+    Unsubscribed,                    // Подписка отменена
+    MouseInfo(MouseInfo),            // Ответ сервера 1
+    DeviceAdded { device: PointingDevice, timestamp: i64 },   // Сервер 1: устройство подключено
+    DeviceRemoved { device: PointingDevice, timestamp: i64 }, // Сервер 1: устройство отключено
+    ProcessInfo(ProcessInfo),        // Ответ сервера 2
+    Processes { processes: Vec<ProcessDetails> }, // Сервер 2: найденные процессы
+    ProcessLookupFailed { reason: LookupFailure, message: String }, // Сервер 2: процесс не получен
+    HostInfo(HostInfo),              // Сервер 2: сведения о системе
+    Pong,                            // Ответ на Ping
+    ServerShutdown { reason: String }, // Сервер останавливается; соединение будет закрыто
+    Busy { retry_after_ms: u64 },    // Достигнут предел подключений; соединение будет закрыто
+    Unauthorized { message: String }, // Клиент не прошёл проверку подлинности; соединение будет закрыто
+    Rejected {                     // Адрес клиента запрещён или превышена частота запросов; соединение будет закрыто
+        reason: RejectReason,
+        message: String,
+        // Когда можно повторить подключение; None - повтор не поможет
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
+    Error { message: String },       // Ошибка обработки запроса
+}
+
+// Токен клиента; в журналы и отладочный вывод значение не попадает
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AuthToken(pub String);
+
+impl std::fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AuthToken(***)")
+    }
+}
+
+// Тип сервера, сообщаемый при приветствии
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerKind {
+    MouseInfo,   // Сервер 1: информация о мыши
+    ProcessInfo, // Сервер 2: информация о процессе
+}
+
+impl std::fmt::Display for ServerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerKind::MouseInfo => write!(f, "информация о мыши"),
+            ServerKind::ProcessInfo => write!(f, "информация о процессе"),
+        }
+    }
+}
+
+// Информация о мыши (сервер 1)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MouseInfo {
+    pub mouse_buttons: u32,           // Количество кнопок мыши
+    pub has_scroll_wheel: bool,       // Наличие колесика мыши
+    pub devices: Vec<PointingDevice>, // Подключенные указывающие устройства
+    pub timestamp: i64,               // Время формирования ответа (секунды UNIX)
+}
+
+// Указывающее устройство (мышь или тачпад)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PointingDevice {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub bus: BusType,
+    pub buttons: u32,           // Количество кнопок
+    pub vertical_wheel: bool,   // Вертикальное колесо прокрутки
+    pub horizontal_wheel: bool, // Горизонтальное колесо прокрутки
+    pub kind: DeviceKind,
+}
+
+// Шина подключения устройства
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusType {
+    Usb,
+    Bluetooth,
+    Ps2,
+    I2c,
+    Virtual,
+    Other,
+}
+
+impl std::fmt::Display for BusType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BusType::Usb => write!(f, "USB"),
+            BusType::Bluetooth => write!(f, "Bluetooth"),
+            BusType::Ps2 => write!(f, "PS/2"),
+            BusType::I2c => write!(f, "I2C"),
+            BusType::Virtual => write!(f, "виртуальная"),
+            BusType::Other => write!(f, "другая"),
+        }
+    }
+}
+
+// Тип указывающего устройства
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Mouse,
+    Touchpad,
+}
+
+impl std::fmt::Display for DeviceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeviceKind::Mouse => write!(f, "мышь"),
+            DeviceKind::Touchpad => write!(f, "тачпад"),
+        }
+    }
+}
+
+// Информация о процессе сервера (сервер 2)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,                         // Идентификатор процесса
+    pub instance_id: String,              // Идентификатор запуска сервера (новый при каждом старте)
+    pub uptime_ms: u64,                   // Время работы сервера в миллисекундах (монотонные часы)
+    pub metrics: Option<ProcessMetrics>,  // Метрики из /proc (None - недоступны на этой ОС)
+    pub timestamp: i64,                   // Время формирования ответа (секунды UNIX)
+}
+
+// Метрики процесса
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessMetrics {
+    pub rss_bytes: u64,            // Резидентная память
+    pub virtual_memory_bytes: u64, // Виртуальная память
+    pub user_cpu_ms: u64,          // Процессорное время в режиме пользователя
+    pub system_cpu_ms: u64,        // Процессорное время в режиме ядра
+    pub threads: u32,              // Количество потоков
+    pub open_fds: u32,             // Количество открытых файловых дескрипторов
+    pub start_time: i64,           // Время запуска процесса (секунды UNIX)
+}
+
+// Сведения о произвольном процессе системы
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub command_line: String,      // Аргументы через пробел; для потоков ядра - [имя]
+    pub state: String,             // Состояние из /proc/<pid>/status, например "S (sleeping)"
+    pub uptime_ms: u64,            // Время работы процесса
+    pub rss_bytes: u64,            // Резидентная память
+    pub virtual_memory_bytes: u64, // Виртуальная память
+    pub owner: String,             // Имя владельца (или UID, если имя неизвестно)
+}
+
+// Причина неудачи при получении сведений о процессе
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LookupFailure {
+    NotFound,         // Процесс не найден
+    PermissionDenied, // Недостаточно прав для чтения сведений
+    Unsupported,      // Сервер не поддерживает поиск на своей ОС
+    Other,
+}
+
+impl std::fmt::Display for LookupFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupFailure::NotFound => write!(f, "процесс не найден"),
+            LookupFailure::PermissionDenied => write!(f, "доступ запрещён"),
+            LookupFailure::Unsupported => write!(f, "не поддерживается"),
+            LookupFailure::Other => write!(f, "ошибка"),
+        }
+    }
+}
+
+// Причина отказа в обслуживании клиента
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    AddressDenied, // Адрес клиента не разрешён настройками сервера
+    RateLimited,   // Превышена допустимая частота подключений или запросов
+}
+
+impl std::fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectReason::AddressDenied => write!(f, "адрес запрещён"),
+            RejectReason::RateLimited => write!(f, "превышена частота запросов"),
+        }
+    }
+}
+
+// Сведения о системе, на которой работает сервер 2
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostInfo {
+    pub hostname: String,
+    pub os: String,                    // Название ОС (sysname из uname)
+    pub kernel_release: String,        // Версия ядра, например "6.8.0-45-generic"
+    pub architecture: String,          // Архитектура процессора (machine из uname)
+    pub uptime_ms: u64,                // Время работы системы с момента загрузки
+    pub load_average: [f64; 3],        // Средняя загрузка за 1, 5 и 15 минут
+    pub total_memory_bytes: u64,       // Общий объём памяти
+    pub available_memory_bytes: u64,   // Доступная память
+    pub cpu_count: u32,                // Количество процессоров в системе
+    pub timestamp: i64,                // Время формирования ответа (секунды UNIX)
+}
@@ -0,0 +1,334 @@
+// TLS для соединений клиента и сервера: загрузка сертификатов, настройки rustls (криптография ring)
+// и блокирующее соединение клиента. Кадры передаются так же, как по TCP
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider, WebPkiSupportedAlgorithms};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{ServerName, UnixTime};
+use rustls::{CertificateError, ClientConnection, DigitallySignedStruct, DistinguishedName, OtherError, RootCertStore,
+             SignatureScheme, StreamOwned};
+
+use crate::codec::wait_for_frame;
+
+pub use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+pub use rustls::{ClientConfig, ServerConfig};
+
+// Размер отпечатка сертификата SHA-256
+pub const FINGERPRINT_SIZE: usize = 32;
+
+// Ошибки настройки TLS и установки защищённого соединения
+#[derive(Debug)]
+pub enum TlsError {
+    Pem(PathBuf, String),     // Файл сертификата или ключа не прочитан (путь и описание)
+    Config(rustls::Error),    // rustls отклонил сертификат, ключ или параметры
+    Fingerprint(String),      // Некорректная запись отпечатка сертификата
+    ServerName(String),       // Имя сервера не подходит для проверки сертификата
+    Handshake(io::Error),     // Ошибка установки соединения (в том числе проверки сертификата)
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Pem(path, e) => write!(f, "{}: {}", path.display(), e),
+            TlsError::Config(e) => write!(f, "некорректные настройки TLS: {}", e),
+            TlsError::Fingerprint(e) => write!(f, "некорректный отпечаток сертификата: {}", e),
+            TlsError::ServerName(name) => write!(f, "некорректное имя сервера для TLS: \"{}\"", name),
+            TlsError::Handshake(e) => write!(f, "ошибка установки TLS-соединения: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TlsError::Config(e) => Some(e),
+            TlsError::Handshake(e) => Some(e),
+            TlsError::Pem(..) | TlsError::Fingerprint(_) | TlsError::ServerName(_) => None,
+        }
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+// Цепочка сертификатов из файла PEM: сначала сертификат владельца, затем промежуточные
+pub fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certificates = CertificateDer::pem_file_iter(path)
+        .and_then(|certificates| certificates.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| TlsError::Pem(path.to_path_buf(), e.to_string()))?;
+    if certificates.is_empty() {
+        return Err(TlsError::Pem(path.to_path_buf(), "в файле нет сертификатов".to_string()));
+    }
+    Ok(certificates)
+}
+
+// Закрытый ключ из файла PEM (PKCS#8, PKCS#1 или SEC1)
+pub fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|e| TlsError::Pem(path.to_path_buf(), e.to_string()))
+}
+
+// Хеш SHA-256 сертификата (отпечаток в двоичном виде)
+pub fn certificate_digest(certificate: &CertificateDer<'_>) -> [u8; FINGERPRINT_SIZE] {
+    let mut digest = [0u8; FINGERPRINT_SIZE];
+    digest.copy_from_slice(::ring::digest::digest(&::ring::digest::SHA256, certificate.as_ref()).as_ref());
+    digest
+}
+
+// Отпечаток SHA-256 сертификата в виде "AB:CD:...", как его выводят openssl и браузеры
+pub fn fingerprint(certificate: &CertificateDer<'_>) -> String {
+    certificate_digest(certificate).iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(":")
+}
+
+// Разбор отпечатка SHA-256: шестнадцатеричные цифры, допускаются разделители ':'
+pub fn parse_fingerprint(text: &str) -> Result<[u8; FINGERPRINT_SIZE], TlsError> {
+    let digits: String = text.trim().chars().filter(|c| *c != ':').collect();
+    if digits.len() != FINGERPRINT_SIZE * 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(TlsError::Fingerprint(format!(
+            "\"{}\": ожидается {} шестнадцатеричных байт SHA-256",
+            text.trim(),
+            FINGERPRINT_SIZE
+        )));
+    }
+    let mut fingerprint = [0u8; FINGERPRINT_SIZE];
+    for (index, byte) in fingerprint.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).unwrap_or_default();
+    }
+    Ok(fingerprint)
+}
+
+// Настройки TLS сервера: сертификат с цепочкой и закрытый ключ.
+// При client_auth сервер запрашивает у клиента сертификат; клиент вправе его не прислать
+pub fn server_config(
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    client_auth: bool,
+) -> Result<ServerConfig, TlsError> {
+    let provider = provider();
+    let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(TlsError::Config)?;
+    let builder = if client_auth {
+        builder.with_client_cert_verifier(Arc::new(ClientCertificate { algorithms: provider.signature_verification_algorithms }))
+    } else {
+        builder.with_no_client_auth()
+    };
+    builder.with_single_cert(certificates, key).map_err(TlsError::Config)
+}
+
+// Сертификат клиента: при рукопожатии проверяется только владение ключом сертификата,
+// сопоставление сертификата с клиентом выполняет сервер по отпечатку после рукопожатия.
+// Так клиент с незарегистрированным сертификатом получает понятный ответ, а не обрыв TLS
+#[derive(Debug)]
+struct ClientCertificate {
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ClientCertVerifier for ClientCertificate {
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+// Способ проверки сертификата сервера клиентом
+#[derive(Debug, Clone)]
+pub enum ServerTrust {
+    Ca(Vec<CertificateDer<'static>>),      // Цепочка до одного из сертификатов CA и имя сервера
+    Pinned(Vec<[u8; FINGERPRINT_SIZE]>),    // Сертификат сервера совпадает с одним из отпечатков
+}
+
+// Сертификат клиента с цепочкой и закрытый ключ для проверки подлинности клиента сервером
+#[derive(Debug)]
+pub struct ClientIdentity {
+    pub certificates: Vec<CertificateDer<'static>>,
+    pub key: PrivateKeyDer<'static>,
+}
+
+// Настройки TLS клиента; identity - сертификат, предъявляемый серверу по его запросу
+pub fn client_config(trust: &ServerTrust, identity: Option<ClientIdentity>) -> Result<ClientConfig, TlsError> {
+    let provider = provider();
+    let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(TlsError::Config)?;
+    let config = match trust {
+        ServerTrust::Ca(certificates) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in certificates {
+                roots.add(certificate.clone()).map_err(TlsError::Config)?;
+            }
+            builder.with_root_certificates(roots)
+        }
+        ServerTrust::Pinned(fingerprints) => builder.dangerous().with_custom_certificate_verifier(Arc::new(PinnedCertificate {
+            fingerprints: fingerprints.clone(),
+            algorithms: provider.signature_verification_algorithms,
+        })),
+    };
+    match identity {
+        Some(identity) => config.with_client_auth_cert(identity.certificates, identity.key).map_err(TlsError::Config),
+        None => Ok(config.with_no_client_auth()),
+    }
+}
+
+// Проверка сертификата сервера по отпечатку: подходит и самоподписанный сертификат,
+// имя сервера и срок действия не проверяются, подпись рукопожатия - проверяется
+#[derive(Debug)]
+struct PinnedCertificate {
+    fingerprints: Vec<[u8; FINGERPRINT_SIZE]>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let digest = certificate_digest(end_entity);
+        if self.fingerprints.contains(&digest) {
+            return Ok(ServerCertVerified::assertion());
+        }
+        let message = format!("отпечаток сертификата сервера {} не совпадает с заданным", fingerprint(end_entity));
+        Err(rustls::Error::InvalidCertificate(CertificateError::Other(OtherError(Arc::new(io::Error::other(message))))))
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, certificate, signature, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+// Соединение клиента с сервером: открытый TCP или TLS поверх TCP
+pub enum Connection {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Connection {
+    // Установка TLS-соединения поверх подключенного сокета; server_name - имя или адрес
+    // из сертификата сервера. Рукопожатие выполняется сразу, чтобы ошибка проверки
+    // сертификата была получена при подключении, а не при первом запросе
+    pub fn tls(mut stream: TcpStream, server_name: &str, config: Arc<ClientConfig>) -> Result<Self, TlsError> {
+        let name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| TlsError::ServerName(server_name.to_string()))?;
+        let mut connection = ClientConnection::new(config, name).map_err(TlsError::Config)?;
+        while connection.is_handshaking() {
+            connection.complete_io(&mut stream).map_err(TlsError::Handshake)?;
+        }
+        Ok(Connection::Tls(Box::new(StreamOwned::new(connection, stream))))
+    }
+
+    pub fn tcp(&self) -> &TcpStream {
+        match self {
+            Connection::Plain(stream) => stream,
+            Connection::Tls(stream) => stream.get_ref(),
+        }
+    }
+
+    // Ожидание начала очередного кадра не дольше timeout (см. wait_for_frame).
+    // Уже расшифрованные, но не прочитанные данные TLS сокет не показывает, поэтому проверяются отдельно
+    pub fn wait_for_frame(&mut self, timeout: Duration) -> io::Result<bool> {
+        if let Connection::Tls(stream) = self {
+            let state = stream.conn.process_new_packets().map_err(io::Error::other)?;
+            if state.plaintext_bytes_to_read() > 0 || state.peer_has_closed() {
+                return Ok(true);
+            }
+        }
+        wait_for_frame(self.tcp(), timeout)
+    }
+
+    // Закрытие соединения; по TLS сервер получает уведомление close_notify
+    pub fn shutdown(&mut self) -> io::Result<()> {
+        if let Connection::Tls(stream) = self {
+            stream.conn.send_close_notify();
+            let _ = stream.flush();
+        }
+        self.tcp().shutdown(Shutdown::Both)
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
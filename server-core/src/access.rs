@@ -0,0 +1,378 @@
+// Ограничение доступа к серверу: списки разрешённых и запрещённых сетей
+// и ограничение частоты подключений и запросов с адреса и от опознанного клиента
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use protocol::{RejectReason, Response};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AccessConfig, RateLimitConfig};
+
+// Как часто из таблиц ограничения частоты удаляются клиенты с полным запасом запросов
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+// Сеть в записи CIDR: "192.168.0.0/16", "2001:db8::/32"; адрес без длины префикса - один узел
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpNetwork {
+    address: IpAddr, // Адрес сети: биты узла обнулены
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.address, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => mask_v4(address.to_bits(), self.prefix) == network.to_bits(),
+            (IpAddr::V6(network), IpAddr::V6(address)) => mask_v6(address.to_bits(), self.prefix) == network.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+// Обнуление битов узла; сдвиг на полную ширину числа не допускается, поэтому префикс 0 отдельно
+fn mask_v4(bits: u32, prefix: u8) -> u32 {
+    if prefix == 0 { 0 } else { bits & (u32::MAX << (32 - prefix)) }
+}
+
+fn mask_v6(bits: u128, prefix: u8) -> u128 {
+    if prefix == 0 { 0 } else { bits & (u128::MAX << (128 - prefix)) }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (address, prefix) = match text.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (text, None),
+        };
+        let address: IpAddr = address.trim().parse().map_err(|_| format!("некорректный адрес сети `{}`", text))?;
+        let address = address.to_canonical();
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => match prefix.trim().parse::<u8>() {
+                Ok(prefix) if prefix <= max_prefix => prefix,
+                _ => return Err(format!("длина префикса в `{}`: ожидается число от 0 до {}", text, max_prefix)),
+            },
+            None => max_prefix,
+        };
+        let address = match address {
+            IpAddr::V4(address) => IpAddr::from(mask_v4(address.to_bits(), prefix).to_be_bytes()),
+            IpAddr::V6(address) => IpAddr::from(mask_v6(address.to_bits(), prefix).to_be_bytes()),
+        };
+        Ok(IpNetwork { address, prefix })
+    }
+}
+
+impl TryFrom<String> for IpNetwork {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, String> {
+        text.parse()
+    }
+}
+
+impl From<IpNetwork> for String {
+    fn from(network: IpNetwork) -> String {
+        network.to_string()
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+// Отказ в обслуживании клиента; сообщается клиенту перед закрытием соединения
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejection {
+    pub reason: RejectReason,
+    pub retry_after: Option<Duration>, // Через сколько пополнится запас запросов
+}
+
+impl Rejection {
+    pub fn retry_after_ms(&self) -> Option<u64> {
+        // Округление вверх: повтор ровно через указанное время не должен снова упереться в предел
+        self.retry_after.map(|delay| delay.as_nanos().div_ceil(1_000_000) as u64)
+    }
+
+    pub fn response(&self) -> Response {
+        let message = match self.reason {
+            RejectReason::AddressDenied => "адрес клиента не разрешён настройками сервера".to_string(),
+            RejectReason::RateLimited => format!(
+                "превышена допустимая частота запросов, повторите через {} мс",
+                self.retry_after_ms().unwrap_or_default()
+            ),
+        };
+        Response::Rejected { reason: self.reason, message, retry_after_ms: self.retry_after_ms() }
+    }
+}
+
+// Проверки, общие для всех сессий сервера
+#[derive(Debug)]
+pub struct Access {
+    allow: Vec<IpNetwork>, // Пусто - разрешены все адреса, кроме запрещённых
+    deny: Vec<IpNetwork>,
+    by_address: Option<RateLimiter<IpAddr>>,
+    by_identity: Option<RateLimiter<String>>,
+}
+
+impl Access {
+    pub fn from_config(access: &AccessConfig, limits: &RateLimitConfig) -> Self {
+        Access {
+            allow: access.allow.clone(),
+            deny: access.deny.clone(),
+            by_address: RateLimiter::new(limits.ip_per_second, limits.ip_burst),
+            by_identity: RateLimiter::new(limits.identity_per_second, limits.identity_burst),
+        }
+    }
+
+    // Есть ли ограничения, о которых стоит сообщить при запуске
+    pub fn enabled(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty() || self.by_address.is_some() || self.by_identity.is_some()
+    }
+
+    // Новое подключение: адрес проверяется по спискам сетей (запрет сильнее разрешения),
+    // затем подключение расходует запрос из запаса адреса
+    pub fn admit(&self, address: IpAddr) -> Result<(), Rejection> {
+        let allowed = self.allow.is_empty() || self.allow.iter().any(|network| network.contains(address));
+        if !allowed || self.deny.iter().any(|network| network.contains(address)) {
+            return Err(Rejection { reason: RejectReason::AddressDenied, retry_after: None });
+        }
+        take(&self.by_address, &address.to_canonical())
+    }
+
+    // Проверки принятого подключения в его сессии
+    pub fn client(self: &Arc<Self>, address: IpAddr) -> ClientAccess {
+        ClientAccess { access: Arc::clone(self), address: address.to_canonical(), identity: None }
+    }
+}
+
+// Проверки одного клиента: его адрес и, после приветствия, имя опознанного клиента
+#[derive(Debug)]
+pub struct ClientAccess {
+    access: Arc<Access>,
+    address: IpAddr,
+    identity: Option<String>,
+}
+
+impl ClientAccess {
+    // Опознанный клиент при приветствии: подключение расходует запрос из запаса клиента
+    pub fn identify(&mut self, identity: &str) -> Result<(), Rejection> {
+        take(&self.access.by_identity, identity)?;
+        self.identity = Some(identity.to_string());
+        Ok(())
+    }
+
+    // Запрос клиента расходует запас и адреса, и опознанного клиента
+    pub fn request(&self) -> Result<(), Rejection> {
+        take(&self.access.by_address, &self.address)?;
+        match &self.identity {
+            Some(identity) => take(&self.access.by_identity, identity.as_str()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn take<K, Q>(limiter: &Option<RateLimiter<K>>, key: &Q) -> Result<(), Rejection>
+where
+    K: Borrow<Q> + Eq + Hash,
+    Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+{
+    match limiter {
+        Some(limiter) => limiter
+            .take(key)
+            .map_err(|retry_after| Rejection { reason: RejectReason::RateLimited, retry_after: Some(retry_after) }),
+        None => Ok(()),
+    }
+}
+
+// Ограничение частоты по алгоритму token bucket: у каждого ключа запас до burst запросов,
+// пополняемый на rate запросов в секунду; запрос при пустом запасе отклоняется
+#[derive(Debug)]
+struct RateLimiter<K> {
+    rate: f64,
+    burst: f64,
+    state: Mutex<Buckets<K>>,
+}
+
+#[derive(Debug)]
+struct Buckets<K> {
+    buckets: HashMap<K, Bucket>,
+    next_prune: Instant,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    // Пополнение запаса за время с прошлого обращения
+    fn refill(&mut self, now: Instant, rate: f64, burst: f64) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated = now;
+    }
+
+    fn take(&mut self, now: Instant, rate: f64, burst: f64) -> Result<(), Duration> {
+        self.refill(now, rate, burst);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    // Нулевая частота - без ограничения
+    fn new(rate: u64, burst: u64) -> Option<Self> {
+        (rate > 0).then(|| RateLimiter {
+            rate: rate as f64,
+            burst: burst as f64,
+            state: Mutex::new(Buckets { buckets: HashMap::new(), next_prune: Instant::now() + PRUNE_INTERVAL }),
+        })
+    }
+
+    // Расход одного запроса; ошибка - через сколько запрос станет возможен
+    fn take<Q>(&self, key: &Q) -> Result<(), Duration>
+    where
+        K: Borrow<Q>,
+        Q: ToOwned<Owned = K> + Eq + Hash + ?Sized,
+    {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if now >= state.next_prune {
+            // Клиент с полным запасом неотличим от нового, его запись не нужна
+            let (rate, burst) = (self.rate, self.burst);
+            state.buckets.retain(|_, bucket| {
+                bucket.refill(now, rate, burst);
+                bucket.tokens < burst
+            });
+            state.next_prune = now + PRUNE_INTERVAL;
+        }
+
+        if let Some(bucket) = state.buckets.get_mut(key) {
+            return bucket.take(now, self.rate, self.burst);
+        }
+        let mut bucket = Bucket { tokens: self.burst, updated: now };
+        let taken = bucket.take(now, self.rate, self.burst);
+        state.buckets.insert(key.to_owned(), bucket);
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(text: &str) -> IpNetwork {
+        text.parse().unwrap()
+    }
+
+    fn address(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn prefix_bounds_are_matched() {
+        assert!(network("0.0.0.0/0").contains(address("203.0.113.7")));
+        assert!(!network("0.0.0.0/0").contains(address("2001:db8::1")), "сеть IPv4 не содержит адресов IPv6");
+        assert!(network("::/0").contains(address("2001:db8::1")));
+        assert!(network("192.168.1.10/32").contains(address("192.168.1.10")));
+        assert!(!network("192.168.1.10/32").contains(address("192.168.1.11")));
+        assert!(network("2001:db8::1/128").contains(address("2001:db8::1")));
+        assert!(!network("2001:db8::1/128").contains(address("2001:db8::2")));
+        // Без длины префикса - один узел
+        assert_eq!(network("10.1.2.3"), network("10.1.2.3/32"));
+        assert_eq!(network("::1"), network("::1/128"));
+    }
+
+    #[test]
+    fn host_bits_are_masked() {
+        assert_eq!(network("192.168.1.77/24").to_string(), "192.168.1.0/24");
+        assert_eq!(network("10.255.255.255/9").to_string(), "10.128.0.0/9");
+        assert_eq!(network("2001:db8:abcd::1/32").to_string(), "2001:db8::/32");
+        assert!(network("192.168.1.77/24").contains(address("192.168.1.200")));
+        assert!(!network("192.168.1.77/24").contains(address("192.168.2.1")));
+    }
+
+    #[test]
+    fn ipv4_mapped_addresses_match_ipv4_networks() {
+        // Сервер, слушающий [::], видит клиентов IPv4 как ::ffff:a.b.c.d
+        assert!(network("10.0.0.0/8").contains(address("::ffff:10.1.2.3")));
+        assert!(!network("10.0.0.0/8").contains(address("::ffff:11.1.2.3")));
+        assert_eq!(network("::ffff:10.1.2.3/32"), network("10.1.2.3/32"));
+    }
+
+    #[test]
+    fn invalid_networks_are_rejected() {
+        for text in ["10.0.0.0/33", "::/129", "10.0.0.0/-1", "10.0.0.0/", "10.0.0/8", "host/8"] {
+            assert!(text.parse::<IpNetwork>().is_err(), "{}", text);
+        }
+        assert!(network("10.0.0.0/ 8").contains(address("10.9.9.9")), "пробелы вокруг частей допускаются");
+    }
+
+    #[test]
+    fn bucket_is_refilled_up_to_burst() {
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 2.0, updated: start };
+        assert!(bucket.take(start, 4.0, 2.0).is_ok());
+        assert!(bucket.take(start, 4.0, 2.0).is_ok());
+        // Запас исчерпан: следующий запрос возможен через 1/4 с
+        assert_eq!(bucket.take(start, 4.0, 2.0), Err(Duration::from_millis(250)));
+
+        let later = start + Duration::from_millis(125); // Пополнено ползапроса
+        assert_eq!(bucket.take(later, 4.0, 2.0), Err(Duration::from_millis(125)));
+        assert!(bucket.take(start + Duration::from_millis(250), 4.0, 2.0).is_ok());
+
+        // После долгого простоя запас не превышает burst
+        bucket.refill(start + Duration::from_secs(60), 4.0, 2.0);
+        assert_eq!(bucket.tokens, 2.0);
+    }
+
+    #[test]
+    fn limiter_keeps_separate_buckets_per_key() {
+        let limiter = RateLimiter::<IpAddr>::new(1, 2).unwrap();
+        let (first, second) = (address("10.0.0.1"), address("10.0.0.2"));
+        assert!(limiter.take(&first).is_ok());
+        assert!(limiter.take(&first).is_ok());
+        let retry_after = limiter.take(&first).unwrap_err();
+        assert!(retry_after > Duration::from_millis(900) && retry_after <= Duration::from_secs(1), "{:?}", retry_after);
+        assert!(limiter.take(&second).is_ok());
+        assert!(RateLimiter::<IpAddr>::new(0, 2).is_none(), "нулевая частота - без ограничения");
+    }
+
+    #[test]
+    fn retry_after_is_rounded_up_to_milliseconds() {
+        let rejection = |nanos| Rejection { reason: RejectReason::RateLimited, retry_after: Some(Duration::from_nanos(nanos)) };
+        assert_eq!(rejection(1_000_000).retry_after_ms(), Some(1));
+        assert_eq!(rejection(1_000_001).retry_after_ms(), Some(2));
+        assert_eq!(rejection(1).retry_after_ms(), Some(1));
+        let denied = Rejection { reason: RejectReason::AddressDenied, retry_after: None };
+        assert_eq!(denied.retry_after_ms(), None);
+        assert!(matches!(
+            rejection(1_500_000).response(),
+            Response::Rejected { reason: RejectReason::RateLimited, retry_after_ms: Some(2), .. }
+        ));
+    }
+
+    #[test]
+    fn deny_list_overrides_allow_list() {
+        let config = AccessConfig { allow: vec![network("10.0.0.0/8")], deny: vec![network("10.0.0.13")] };
+        let access = Access::from_config(&config, &RateLimitConfig::default());
+        assert!(access.admit(address("10.0.0.12")).is_ok());
+        assert_eq!(access.admit(address("10.0.0.13")).unwrap_err().reason, RejectReason::AddressDenied);
+        assert_eq!(access.admit(address("192.168.0.1")).unwrap_err().reason, RejectReason::AddressDenied);
+    }
+}
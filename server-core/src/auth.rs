@@ -0,0 +1,79 @@
+// Проверка подлинности клиентов: токен из приветствия или сертификат клиента TLS.
+// Опознанный клиент получает имя, под которым его обработка записывается в журнал
+use protocol::tls::{parse_fingerprint, FINGERPRINT_SIZE};
+use protocol::AuthToken;
+use ring::digest::{digest, SHA256};
+
+use crate::config::AuthConfig;
+
+type Digest = [u8; FINGERPRINT_SIZE];
+
+// Зарегистрированные клиенты. Хранятся хеши токенов: сравнение хешей не выдаёт
+// по времени ответа, сколько символов токена совпало
+#[derive(Debug, Default)]
+pub struct Authenticator {
+    tokens: Vec<(String, Digest)>,       // Имя клиента и хеш SHA-256 его токена
+    certificates: Vec<(String, Digest)>, // Имя клиента и отпечаток его сертификата
+}
+
+impl Authenticator {
+    // Настройки уже проверены при загрузке, некорректные отпечатки сюда не попадают
+    pub fn from_config(config: &AuthConfig) -> Self {
+        let mut authenticator = Authenticator::default();
+        for client in &config.clients {
+            if let Some(token) = &client.token {
+                authenticator.tokens.push((client.name.clone(), token_digest(token)));
+            }
+            if let Some(certificate) = client.certificate.as_deref().and_then(|text| parse_fingerprint(text).ok()) {
+                authenticator.certificates.push((client.name.clone(), certificate));
+            }
+        }
+        authenticator
+    }
+
+    // Без зарегистрированных клиентов подлинность не проверяется
+    pub fn enabled(&self) -> bool {
+        !self.tokens.is_empty() || !self.certificates.is_empty()
+    }
+
+    // Есть ли клиенты, опознаваемые по токену
+    pub fn accepts_tokens(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    // Нужно ли запрашивать у клиентов сертификат при рукопожатии TLS
+    pub fn requests_certificates(&self) -> bool {
+        !self.certificates.is_empty()
+    }
+
+    // Имя клиента по токену и отпечатку сертификата; None - проверка отключена.
+    // Ошибка содержит причину отказа, сообщаемую клиенту
+    pub fn authenticate(&self, token: Option<&AuthToken>, certificate: Option<&Digest>) -> Result<Option<String>, String> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let by_certificate = certificate.and_then(|certificate| find(&self.certificates, certificate));
+        let by_token = match token {
+            Some(token) => Some(find(&self.tokens, &token_digest(&token.0)).ok_or("неверный токен клиента")?),
+            None => None,
+        };
+        match (by_certificate, by_token) {
+            (Some(first), Some(second)) if first != second => {
+                Err("токен и сертификат принадлежат разным клиентам".to_string())
+            }
+            (Some(name), _) | (None, Some(name)) => Ok(Some(name.to_string())),
+            (None, None) if certificate.is_some() => Err("сертификат клиента не зарегистрирован на сервере".to_string()),
+            (None, None) => Err("сервер требует токен или сертификат клиента".to_string()),
+        }
+    }
+}
+
+fn token_digest(token: &str) -> Digest {
+    let mut result = Digest::default();
+    result.copy_from_slice(digest(&SHA256, token.as_bytes()).as_ref());
+    result
+}
+
+fn find<'a>(clients: &'a [(String, Digest)], digest: &Digest) -> Option<&'a str> {
+    clients.iter().find(|(_, known)| known == digest).map(|(name, _)| name.as_str())
+}
@@ -0,0 +1,550 @@
+// Настройки сервера. Источники по возрастанию приоритета: значения по умолчанию,
+// файл TOML (--config), переменные окружения <ПРЕФИКС>_*, ключи командной строки
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use clap::{Args, CommandFactory, FromArgMatches, Parser};
+use logging::{LogFormat, Rotation, RotationPeriod};
+use protocol::tls::parse_fingerprint;
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::access::IpNetwork;
+use crate::ServerSpec;
+
+// Значения по умолчанию (адрес и файл журнала задаются описанием сервера)
+const DEFAULT_MAX_CLIENTS: u64 = 5;
+const DEFAULT_KEEPALIVE_MS: u64 = 15_000;
+const DEFAULT_READ_TIMEOUT_MS: u64 = 30_000; // Клиент присылает Ping каждые 10 с
+const DEFAULT_WRITE_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_SHUTDOWN_GRACE_MS: u64 = 5_000;
+const DEFAULT_MIN_INTERVAL_MS: u64 = 100;
+const DEFAULT_MAX_INTERVAL_MS: u64 = 60_000;
+const DEFAULT_RATE_BURST: u64 = 20;
+// Наибольший предел клиентов: при остановке сервер ждёт освобождения всех мест одним запросом на u32 мест
+const MAX_CLIENTS_LIMIT: u64 = u32::MAX as u64;
+// Значение токенов клиентов в выводе настроек
+const REDACTED_TOKEN: &str = "<скрыт>";
+
+// Ключи командной строки. Переменная окружения с тем же значением добавляется
+// каждому ключу при разборе: --max-clients - <ПРЕФИКС>_MAX_CLIENTS
+#[derive(Debug, Clone, Parser)]
+pub struct Cli<A: Args> {
+    #[arg(long, short, value_name = "PATH", help = "Файл настроек в формате TOML")]
+    config: Option<PathBuf>,
+
+    #[arg(long, value_name = "ADDR", value_delimiter = ',',
+          help = "Адрес для приёма подключений, например 0.0.0.0:7878 или [::]:7878 (можно указать несколько)")]
+    listen: Vec<SocketAddr>,
+
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Предел одновременно обслуживаемых клиентов")]
+    max_clients: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Простой соединения до первой проверки TCP keepalive")]
+    keepalive_ms: Option<u64>,
+
+    #[arg(long, value_name = "PATH", help = "Сертификат сервера с цепочкой в формате PEM; включает TLS")]
+    tls_cert: Option<PathBuf>,
+
+    #[arg(long, value_name = "PATH", help = "Закрытый ключ сертификата сервера в формате PEM")]
+    tls_key: Option<PathBuf>,
+
+    #[arg(long, value_name = "NAME=TOKEN", value_delimiter = ',', value_parser = parse_auth_token,
+          help = "Токен клиента с его именем; с ним сервер отклоняет клиентов без токена (можно указать несколько)")]
+    auth_token: Vec<(String, String)>,
+
+    #[arg(long, value_name = "CIDR", value_delimiter = ',',
+          help = "Сети, клиентам из которых разрешено подключение, например 192.168.0.0/16 (можно указать несколько)")]
+    allow: Vec<IpNetwork>,
+
+    #[arg(long, value_name = "CIDR", value_delimiter = ',', help = "Сети, клиентам из которых подключение запрещено")]
+    deny: Vec<IpNetwork>,
+
+    #[arg(long, value_name = "N", help = "Подключений и запросов в секунду с одного адреса (0 - без ограничения)")]
+    ip_rate_limit: Option<u64>,
+
+    #[arg(long, value_name = "N", help = "Подключений и запросов в секунду от одного опознанного клиента (0 - без ограничения)")]
+    identity_rate_limit: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Молчание клиента, после которого соединение закрывается")]
+    read_timeout_ms: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Предельная длительность отправки одного ответа")]
+    write_timeout_ms: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Сколько при остановке ждать отключения клиентов")]
+    shutdown_grace_ms: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Наименьший интервал рассылки по подписке")]
+    min_interval_ms: Option<u64>,
+
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..),
+          help = "Наибольший интервал рассылки по подписке")]
+    max_interval_ms: Option<u64>,
+
+    #[command(flatten)]
+    pub provider: A, // Ключи источника данных
+
+    #[arg(long, value_name = "PATH", help = "Файл журнала")]
+    log_file: Option<PathBuf>,
+
+    #[arg(long, value_name = "FILTER", help = "Фильтр уровней журнала, например info или warn,server_core=debug")]
+    log_level: Option<String>,
+
+    #[arg(long, value_name = "FORMAT", help = "Формат журнала: plain или json")]
+    log_format: Option<LogFormat>,
+
+    #[arg(long, value_name = "BYTES", help = "Размер журнала, после которого начинается новый файл (0 - без ограничения)")]
+    log_max_size_bytes: Option<u64>,
+
+    #[arg(long, value_name = "PERIOD", help = "Ротация журнала по времени: never, hourly или daily")]
+    log_rotation: Option<RotationPeriod>,
+
+    #[arg(long, value_name = "N", help = "Число хранимых старых файлов журнала")]
+    log_keep: Option<usize>,
+
+    #[arg(long, value_name = "BOOL", help = "Сжатие старых файлов журнала в gzip")]
+    log_compress: Option<bool>,
+
+    #[arg(long, help = "Вывести итоговые настройки в формате TOML и завершить работу")]
+    pub print_config: bool,
+}
+
+// Разбор значения --auth-token: имя клиента и токен через '='
+fn parse_auth_token(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, token)) if !name.trim().is_empty() && !token.is_empty() => {
+            Ok((name.trim().to_string(), token.to_string()))
+        }
+        _ => Err("ожидается ИМЯ=ТОКЕН".to_string()),
+    }
+}
+
+impl<A: Args> Cli<A> {
+    // Разбор командной строки с именами и переменными окружения сервера
+    pub fn parse_for(spec: &ServerSpec) -> Self {
+        let command = Cli::<A>::command()
+            .name(spec.name)
+            .version(spec.version)
+            .about(spec.about)
+            .mut_args(|arg| {
+                if !arg.get_action().takes_values() || arg.get_env().is_some() {
+                    return arg;
+                }
+                let env = format!("{}_{}", spec.env_prefix, arg.get_id().as_str().to_uppercase());
+                arg.env(env)
+            });
+        let matches = command.get_matches();
+        Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+}
+
+// Ключи командной строки источника без собственных настроек
+#[derive(Debug, Clone, Default, Args)]
+pub struct NoArgs {}
+
+// Настройки источника данных: таблица файла настроек и ключи командной строки
+// (у общего сервера с несколькими источниками таблиц несколько)
+pub trait ProviderSettings: Default + Clone + fmt::Debug + PartialEq + Send + Sync + 'static {
+    const SECTIONS: &'static [&'static str] = &[]; // Имена таблиц TOML; пусто - настроек у источника нет
+    type Args: Args + Clone + fmt::Debug + Send + Sync + 'static;
+
+    // Переопределение значениями из командной строки и переменных окружения
+    fn apply(&mut self, args: &Self::Args);
+
+    // Проверка итоговых значений; в ошибке указывается ключ файла настроек
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // Чтение таблицы name из SECTIONS: её содержимое - очередное значение map
+    fn read_section<'de, M: MapAccess<'de>>(&mut self, _name: &str, map: &mut M) -> Result<(), M::Error> {
+        map.next_value::<de::IgnoredAny>().map(drop)
+    }
+
+    // Запись таблиц SECTIONS для --print-config
+    fn write_sections<M: SerializeMap>(&self, _map: &mut M) -> Result<(), M::Error> {
+        Ok(())
+    }
+}
+
+impl ProviderSettings for () {
+    type Args = NoArgs;
+
+    fn apply(&mut self, _args: &NoArgs) {}
+}
+
+// Итоговые настройки сервера; разделы соответствуют таблицам файла TOML
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config<S> {
+    pub network: NetworkConfig,
+    pub tls: TlsConfig,
+    pub auth: AuthConfig,
+    pub access: AccessConfig,
+    pub rate_limit: RateLimitConfig,
+    pub timeouts: TimeoutConfig,
+    pub subscription: SubscriptionConfig,
+    pub provider: S, // Таблицы ProviderSettings::SECTIONS
+    pub log: LogConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub listen: Vec<SocketAddr>, // Адреса для приёма подключений (IPv4 и IPv6); пусто - адрес сервера по умолчанию
+    pub max_clients: u64,        // Предел одновременно обслуживаемых клиентов
+    pub keepalive_ms: u64,       // Простой соединения до первой проверки TCP keepalive
+}
+
+// TLS на всех адресах сервера; без сертификата соединения не шифруются
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_file: Option<PathBuf>, // Сертификат сервера, за ним промежуточные (PEM)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,  // Закрытый ключ сертификата (PEM)
+}
+
+// Клиенты, допущенные к серверу; пустой список - подлинность клиентов не проверяется
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub clients: Vec<AuthClient>, // Таблицы [[auth.clients]]
+}
+
+impl AuthConfig {
+    // Копия для вывода настроек: токены заменены заглушкой, вывод попадает в терминал и журналы
+    fn redacted(&self) -> Self {
+        let mut redacted = self.clone();
+        for client in &mut redacted.clients {
+            if let Some(token) = &mut client.token {
+                *token = REDACTED_TOKEN.to_string();
+            }
+        }
+        redacted
+    }
+}
+
+// Клиент опознаётся по токену в приветствии или по сертификату TLS (задаётся хотя бы одно)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthClient {
+    pub name: String, // Имя клиента в журнале сервера
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<String>, // Отпечаток SHA-256 сертификата клиента, "AB:CD:..."
+}
+
+// Адреса клиентов, которым разрешено подключение; запрет сильнее разрешения
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessConfig {
+    pub allow: Vec<IpNetwork>, // Пусто - разрешены все адреса, кроме deny
+    pub deny: Vec<IpNetwork>,
+}
+
+// Ограничение частоты подключений и запросов: запас до *_burst запросов,
+// пополняемый на *_per_second запросов в секунду; 0 в *_per_second - без ограничения
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub ip_per_second: u64,       // С одного адреса клиента
+    pub ip_burst: u64,
+    pub identity_per_second: u64, // От одного клиента, опознанного по токену или сертификату
+    pub identity_burst: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeoutConfig {
+    pub read_ms: u64,           // Молчание клиента, после которого он считается отключившимся
+    pub write_ms: u64,          // Предельная длительность отправки одного ответа
+    pub shutdown_grace_ms: u64, // Ожидание отключения клиентов при остановке
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubscriptionConfig {
+    pub min_interval_ms: u64, // Границы интервала рассылки, запрошенного клиентом
+    pub max_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub file: PathBuf,      // Пусто - файл сервера по умолчанию
+    pub level: String,      // Фильтр уровней: "info", "debug", "warn,server_core=debug" и т. п.
+    pub format: LogFormat,  // Текст или JSON построчно
+    pub rotation: Rotation, // Таблица [log.rotation]
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig { listen: Vec::new(), max_clients: DEFAULT_MAX_CLIENTS, keepalive_ms: DEFAULT_KEEPALIVE_MS }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig { ip_per_second: 0, ip_burst: DEFAULT_RATE_BURST, identity_per_second: 0, identity_burst: DEFAULT_RATE_BURST }
+    }
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        TimeoutConfig {
+            read_ms: DEFAULT_READ_TIMEOUT_MS,
+            write_ms: DEFAULT_WRITE_TIMEOUT_MS,
+            shutdown_grace_ms: DEFAULT_SHUTDOWN_GRACE_MS,
+        }
+    }
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        SubscriptionConfig { min_interval_ms: DEFAULT_MIN_INTERVAL_MS, max_interval_ms: DEFAULT_MAX_INTERVAL_MS }
+    }
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            file: PathBuf::new(),
+            level: logging::DEFAULT_FILTER.to_string(),
+            format: LogFormat::default(),
+            rotation: Rotation::default(),
+        }
+    }
+}
+
+impl<S: ProviderSettings> Config<S> {
+    // Настройки из всех источников с проверкой значений
+    pub fn load(cli: &Cli<S::Args>, spec: &ServerSpec) -> Result<Self, String> {
+        let mut config = match &cli.config {
+            Some(path) => Config::from_file(path)?,
+            None => Config::default(),
+        };
+        config.apply(cli);
+        if config.network.listen.is_empty() {
+            config.network.listen = vec![SocketAddr::from((Ipv4Addr::UNSPECIFIED, spec.default_port))];
+        }
+        if config.log.file.as_os_str().is_empty() {
+            config.log.file = PathBuf::from(spec.default_log_file);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        // Путь к значению ("network.max_clients") указывается в ошибке вместе с позицией в файле
+        serde_path_to_error::deserialize(toml::Deserializer::new(&text)).map_err(|e| match e.path().to_string() {
+            key if key == "." => format!("{}: {}", path.display(), e.into_inner()), // Ошибка на верхнем уровне файла
+            key => format!("{}: {}: {}", path.display(), key, e.into_inner()),
+        })
+    }
+
+    // Переопределение значениями из командной строки и переменных окружения
+    fn apply(&mut self, cli: &Cli<S::Args>) {
+        if !cli.listen.is_empty() {
+            self.network.listen = cli.listen.clone();
+        }
+        let overrides = [
+            (&mut self.network.max_clients, cli.max_clients),
+            (&mut self.network.keepalive_ms, cli.keepalive_ms),
+            (&mut self.rate_limit.ip_per_second, cli.ip_rate_limit),
+            (&mut self.rate_limit.identity_per_second, cli.identity_rate_limit),
+            (&mut self.timeouts.read_ms, cli.read_timeout_ms),
+            (&mut self.timeouts.write_ms, cli.write_timeout_ms),
+            (&mut self.timeouts.shutdown_grace_ms, cli.shutdown_grace_ms),
+            (&mut self.subscription.min_interval_ms, cli.min_interval_ms),
+            (&mut self.subscription.max_interval_ms, cli.max_interval_ms),
+        ];
+        for (field, value) in overrides {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        if let Some(cert_file) = &cli.tls_cert {
+            self.tls.cert_file = Some(cert_file.clone());
+        }
+        if let Some(key_file) = &cli.tls_key {
+            self.tls.key_file = Some(key_file.clone());
+        }
+        for (name, token) in &cli.auth_token {
+            match self.auth.clients.iter_mut().find(|client| client.name == *name) {
+                Some(client) => client.token = Some(token.clone()),
+                None => self.auth.clients.push(AuthClient { name: name.clone(), token: Some(token.clone()), certificate: None }),
+            }
+        }
+        if !cli.allow.is_empty() {
+            self.access.allow = cli.allow.clone();
+        }
+        if !cli.deny.is_empty() {
+            self.access.deny = cli.deny.clone();
+        }
+        self.provider.apply(&cli.provider);
+        if let Some(file) = &cli.log_file {
+            self.log.file = file.clone();
+        }
+        if let Some(level) = &cli.log_level {
+            self.log.level = level.clone();
+        }
+        if let Some(format) = cli.log_format {
+            self.log.format = format;
+        }
+        let rotation = &mut self.log.rotation;
+        if let Some(max_size_bytes) = cli.log_max_size_bytes {
+            rotation.max_size_bytes = max_size_bytes;
+        }
+        if let Some(period) = cli.log_rotation {
+            rotation.period = period;
+        }
+        if let Some(keep) = cli.log_keep {
+            rotation.keep = keep;
+        }
+        if let Some(compress) = cli.log_compress {
+            rotation.compress = compress;
+        }
+    }
+
+    // Проверка итоговых значений; в ошибке указывается ключ файла настроек
+    fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("network.max_clients", self.network.max_clients),
+            ("network.keepalive_ms", self.network.keepalive_ms),
+            ("rate_limit.ip_burst", self.rate_limit.ip_burst),
+            ("rate_limit.identity_burst", self.rate_limit.identity_burst),
+            ("timeouts.read_ms", self.timeouts.read_ms),
+            ("timeouts.write_ms", self.timeouts.write_ms),
+            ("timeouts.shutdown_grace_ms", self.timeouts.shutdown_grace_ms),
+            ("subscription.min_interval_ms", self.subscription.min_interval_ms),
+            ("subscription.max_interval_ms", self.subscription.max_interval_ms),
+        ];
+        if let Some((key, _)) = positive.iter().find(|(_, value)| *value == 0) {
+            return Err(format!("{}: ожидается положительное число", key));
+        }
+        if self.network.max_clients > MAX_CLIENTS_LIMIT {
+            return Err(format!("network.max_clients: ожидается число не больше {}", MAX_CLIENTS_LIMIT));
+        }
+        if self.subscription.min_interval_ms > self.subscription.max_interval_ms {
+            return Err(format!(
+                "subscription.min_interval_ms: {} больше subscription.max_interval_ms ({})",
+                self.subscription.min_interval_ms, self.subscription.max_interval_ms
+            ));
+        }
+        match (&self.tls.cert_file, &self.tls.key_file) {
+            (Some(_), None) => return Err("tls.key_file: не задан ключ для tls.cert_file".to_string()),
+            (None, Some(_)) => return Err("tls.cert_file: не задан сертификат для tls.key_file".to_string()),
+            _ => {}
+        }
+        self.validate_auth()?;
+        self.provider.validate()?;
+        logging::parse_filter(&self.log.level).map_err(|e| format!("log.level: {}", e))?;
+        Ok(())
+    }
+
+    fn validate_auth(&self) -> Result<(), String> {
+        let clients = &self.auth.clients;
+        for (index, client) in clients.iter().enumerate() {
+            let name = &client.name;
+            if name.trim().is_empty() {
+                return Err("auth.clients: пустое имя клиента".to_string());
+            }
+            if clients[..index].iter().any(|other| other.name == *name) {
+                return Err(format!("auth.clients: клиент `{}` указан дважды", name));
+            }
+            match (&client.token, &client.certificate) {
+                (None, None) => return Err(format!("auth.clients: у клиента `{}` нет ни токена, ни сертификата", name)),
+                (Some(token), _) if token.is_empty() => {
+                    return Err(format!("auth.clients: пустой токен клиента `{}`", name));
+                }
+                (Some(token), _) => {
+                    if let Some(other) = clients[..index].iter().find(|other| other.token.as_ref() == Some(token)) {
+                        return Err(format!("auth.clients: у клиентов `{}` и `{}` одинаковый токен", other.name, name));
+                    }
+                }
+                (None, Some(_)) => {}
+            }
+            if let Some(certificate) = &client.certificate {
+                parse_fingerprint(certificate).map_err(|e| format!("auth.clients: клиент `{}`: {}", name, e))?;
+                if self.tls.cert_file.is_none() {
+                    return Err(format!("auth.clients: сертификат клиента `{}` требует TLS (tls.cert_file)", name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Настройки в формате TOML для --print-config
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("настройки всегда представимы в TOML")
+    }
+}
+
+// Таблица источника данных называется по-разному у разных серверов,
+// поэтому чтение и запись настроек реализованы вручную. Запись служит только для вывода
+// настроек, поэтому токены клиентов в ней скрыты
+impl<S: ProviderSettings> Serialize for Config<S> {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("network", &self.network)?;
+        map.serialize_entry("tls", &self.tls)?;
+        map.serialize_entry("auth", &self.auth.redacted())?;
+        map.serialize_entry("access", &self.access)?;
+        map.serialize_entry("rate_limit", &self.rate_limit)?;
+        map.serialize_entry("timeouts", &self.timeouts)?;
+        map.serialize_entry("subscription", &self.subscription)?;
+        self.provider.write_sections(&mut map)?;
+        map.serialize_entry("log", &self.log)?;
+        map.end()
+    }
+}
+
+impl<'de, S: ProviderSettings> Deserialize<'de> for Config<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ConfigVisitor(PhantomData))
+    }
+}
+
+struct ConfigVisitor<S>(PhantomData<S>);
+
+impl<'de, S: ProviderSettings> Visitor<'de> for ConfigVisitor<S> {
+    type Value = Config<S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("таблица настроек сервера")
+    }
+
+    // Отсутствующие таблицы получают значения по умолчанию, неизвестные считаются ошибкой
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Config<S>, M::Error> {
+        let mut config = Config::<S>::default();
+        while let Some(section) = map.next_key::<String>()? {
+            match section.as_str() {
+                "network" => config.network = map.next_value()?,
+                "tls" => config.tls = map.next_value()?,
+                "auth" => config.auth = map.next_value()?,
+                "access" => config.access = map.next_value()?,
+                "rate_limit" => config.rate_limit = map.next_value()?,
+                "timeouts" => config.timeouts = map.next_value()?,
+                "subscription" => config.subscription = map.next_value()?,
+                "log" => config.log = map.next_value()?,
+                name if S::SECTIONS.contains(&name) => config.provider.read_section(name, &mut map)?,
+                name => return Err(de::Error::custom(format!("неизвестная таблица `{}`", name))),
+            }
+        }
+        Ok(config)
+    }
+}
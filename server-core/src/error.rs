@@ -0,0 +1,38 @@
+// Ошибки запуска сервера; по любой из них процесс завершается с кодом 1.
+// Ошибки обработки отдельных клиентов записываются в журнал и не останавливают сервер
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+
+use protocol::tls::TlsError;
+
+#[derive(Debug)]
+pub enum ServerError {
+    Config(String),              // Некорректные настройки (ключ и описание)
+    Runtime(io::Error),          // Не удалось запустить асинхронную среду выполнения
+    Bind(SocketAddr, io::Error), // Не удалось открыть адрес для приёма подключений
+    Tls(TlsError),               // Не удалось загрузить сертификат или ключ TLS
+    Logging(String),             // Журнал не удалось установить ни в файл, ни в stderr
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Config(e) => write!(f, "Ошибка в настройках: {}", e),
+            ServerError::Runtime(e) => write!(f, "Не удалось запустить среду выполнения: {}", e),
+            ServerError::Bind(addr, e) => write!(f, "Не удалось открыть адрес {}: {}", addr, e),
+            ServerError::Tls(e) => write!(f, "Ошибка настройки TLS: {}", e),
+            ServerError::Logging(e) => write!(f, "Не удалось установить журнал: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServerError::Runtime(e) | ServerError::Bind(_, e) => Some(e),
+            ServerError::Tls(e) => Some(e),
+            ServerError::Config(_) | ServerError::Logging(_) => None,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// Общая часть серверов: настройки, приём подключений, сессии клиентов, журнал и остановка.
+// Сервер задаёт описание (ServerSpec) и источник данных (DataProvider) и вызывает run;
+// сервер с несколькими источниками вызывает run_services
+mod access;
+mod auth;
+mod config;
+mod error;
+mod listener;
+mod server;
+mod session;
+mod tls;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use protocol::{Request, Response, ServerKind};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+pub use access::IpNetwork;
+pub use config::{
+    AccessConfig, AuthClient, AuthConfig, Cli, Config, LogConfig, NetworkConfig, NoArgs, ProviderSettings, RateLimitConfig,
+    SubscriptionConfig, TimeoutConfig, TlsConfig,
+};
+pub use error::ServerError;
+pub use server::{run, run_services, MAX_PENDING_REJECTS};
+
+// Описание сервера: имена, переменные окружения и значения по умолчанию
+#[derive(Debug, Clone, Copy)]
+pub struct ServerSpec {
+    pub name: &'static str,             // Имя программы: "server1"
+    pub title: &'static str,            // Название в сообщениях консоли: "Сервер 1"
+    pub about: &'static str,            // Описание для --help
+    pub version: &'static str,          // Версия для --version
+    pub env_prefix: &'static str,       // Префикс переменных окружения: "SERVER1"
+    pub default_port: u16,              // Порт на всех адресах IPv4, если network.listen не задан
+    pub default_log_file: &'static str, // Файл журнала, если log.file не задан
+}
+
+// Источник данных сервера, общий для всех сессий
+pub trait DataProvider: Send + Sync + 'static {
+    // Тип сервера, сообщаемый клиенту в приветствии
+    fn kind(&self) -> ServerKind;
+
+    // Возможности сверх общих get и subscribe
+    fn capabilities(&self) -> Vec<String>;
+
+    // Текущие данные: ответ на Get и очередная рассылка по подписке.
+    // Вызывается в потоке для блокирующих операций, поэтому может читать файлы;
+    // вызовы для разных клиентов выполняются одновременно
+    fn snapshot(&self) -> Response;
+
+    // Запросы, специфичные для источника; None - запрос не поддерживается.
+    // Вызывается, как и snapshot, в потоке для блокирующих операций
+    fn handle(&self, _request: &Request) -> Option<Response> {
+        None
+    }
+
+    // События, пересылаемые подписчику сразу (например, подключение устройств);
+    // подписка на них снимается удалением приёмника
+    fn events(&self) -> Option<UnboundedReceiver<Response>> {
+        None
+    }
+
+    // Запуск фоновой работы источника после установки журнала
+    fn start(&self) {}
+}
+
+// Источники данных на общих адресах. Клиент выбирает источник типом сервера в приветствии;
+// запросы, не поддерживаемые выбранным источником, передаются остальным источникам службы
+pub struct Service {
+    pub providers: Vec<Arc<dyn DataProvider>>, // Первый - для клиентов, не указавших тип сервера
+    pub listen: Option<Vec<SocketAddr>>,       // None - адреса network.listen
+}
+
+impl Service {
+    // Служба из одного источника на адресах network.listen
+    pub fn single(provider: Arc<dyn DataProvider>) -> Self {
+        Service { providers: vec![provider], listen: None }
+    }
+}
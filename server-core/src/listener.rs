@@ -0,0 +1,39 @@
+// Приём подключений на нескольких адресах
+use std::net::SocketAddr;
+use std::task::Poll;
+
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::{TcpListener, TcpStream};
+
+// Очередь ещё не принятых соединений на каждом адресе
+const LISTEN_BACKLOG: i32 = 1024;
+
+// Сокет для приёма подключений. Сокет IPv6 принимает только IPv6,
+// чтобы адреса 0.0.0.0 и [::] с одним портом можно было слушать одновременно
+pub fn bind_listener(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?; // Повторный запуск не ждёт освобождения порта после TIME_WAIT
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    TcpListener::from_std(socket.into())
+}
+
+// Очередное подключение на любом из прослушиваемых адресов вместе с номером адреса
+pub async fn accept_any(listeners: &[TcpListener]) -> std::io::Result<(usize, TcpStream, SocketAddr)> {
+    std::future::poll_fn(|cx| {
+        listeners
+            .iter()
+            .enumerate()
+            .find_map(|(index, listener)| match listener.poll_accept(cx) {
+                Poll::Ready(accepted) => Some(Poll::Ready(accepted.map(|(stream, addr)| (index, stream, addr)))),
+                Poll::Pending => None,
+            })
+            .unwrap_or(Poll::Pending)
+    })
+    .await
+}
@@ -0,0 +1,263 @@
+// Запуск сервера: настройки, журнал, приём подключений и штатная остановка
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use logging::Logging;
+use protocol::Response;
+use tokio::net::TcpStream;
+use tokio::sync::{watch, Semaphore};
+use tokio::time::{sleep, timeout};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info, warn, Instrument, Span};
+
+use crate::access::Access;
+use crate::auth::Authenticator;
+use crate::config::{Cli, Config, ProviderSettings};
+use crate::error::ServerError;
+use crate::listener::{accept_any, bind_listener};
+use crate::session::{handle_client, reject, ConnectionSettings};
+use crate::{tls, DataProvider, ServerSpec, Service};
+
+// Пауза после ошибки приёма соединения (например, исчерпан лимит дескрипторов)
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+// Через сколько клиенту, отклонённому сверх предела, предлагается повторить подключение
+const BUSY_RETRY_AFTER_MS: u64 = 5_000;
+// Число отказов, отправляемых одновременно (каждый - рукопожатие TLS и ожидание закрытия клиентом)
+pub const MAX_PENDING_REJECTS: usize = 32;
+
+impl ConnectionSettings {
+    fn from_config<S>(config: &Config<S>) -> Self {
+        ConnectionSettings {
+            max_clients: config.network.max_clients as usize,
+            read_timeout: Duration::from_millis(config.timeouts.read_ms),
+            write_timeout: Duration::from_millis(config.timeouts.write_ms),
+            keepalive: Duration::from_millis(config.network.keepalive_ms),
+            min_interval_ms: config.subscription.min_interval_ms,
+            max_interval_ms: config.subscription.max_interval_ms,
+        }
+    }
+}
+
+// Точка входа сервера: источник данных создаётся по его разделу настроек.
+// Ошибка запуска выводится в stderr, процесс завершается с кодом 1
+pub fn run<S, F>(spec: &'static ServerSpec, build: F) -> ExitCode
+where
+    S: ProviderSettings,
+    F: FnOnce(&S) -> Result<Arc<dyn DataProvider>, String>,
+{
+    run_services(spec, |settings: &S| Ok(vec![Service::single(build(settings)?)]))
+}
+
+// Точка входа сервера с несколькими источниками: каждая служба слушает свои адреса
+pub fn run_services<S, F>(spec: &'static ServerSpec, build: F) -> ExitCode
+where
+    S: ProviderSettings,
+    F: FnOnce(&S) -> Result<Vec<Service>, String>,
+{
+    match start(spec, build) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn start<S, F>(spec: &'static ServerSpec, build: F) -> Result<(), ServerError>
+where
+    S: ProviderSettings,
+    F: FnOnce(&S) -> Result<Vec<Service>, String>,
+{
+    let cli = Cli::<S::Args>::parse_for(spec);
+    let config = Config::<S>::load(&cli, spec).map_err(ServerError::Config)?;
+    let services = build(&config.provider).map_err(ServerError::Config)?;
+    if cli.print_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+
+    let runtime = tokio::runtime::Runtime::new().map_err(ServerError::Runtime)?; // Асинхронное выполнение
+    runtime.block_on(serve(spec, cli, config, services))
+}
+
+async fn serve<S: ProviderSettings>(
+    spec: &'static ServerSpec,
+    cli: Cli<S::Args>,
+    config: Config<S>,
+    services: Vec<Service>,
+) -> Result<(), ServerError> {
+    let settings = ConnectionSettings::from_config(&config);
+    let auth = Arc::new(Authenticator::from_config(&config.auth));
+    let access = Arc::new(Access::from_config(&config.access, &config.rate_limit));
+    let tls = tls::acceptor(&config.tls, auth.requests_certificates()).map_err(ServerError::Tls)?;
+
+    // Для каждого адреса - источники службы, которая его слушает
+    let mut listeners = Vec::new();
+    let mut routes: Vec<Arc<[Arc<dyn DataProvider>]>> = Vec::new();
+    let mut listen = Vec::new();
+    for service in &services {
+        let providers: Arc<[Arc<dyn DataProvider>]> = service.providers.clone().into();
+        for &addr in service.listen.as_ref().unwrap_or(&config.network.listen) {
+            listeners.push(bind_listener(addr).map_err(|e| ServerError::Bind(addr, e))?);
+            routes.push(Arc::clone(&providers));
+            listen.push(addr);
+        }
+    }
+    let addresses: Vec<String> = listen.iter().map(|addr| addr.to_string()).collect();
+    println!("{} запущен на {}, клиентов не более {}", spec.title, addresses.join(", "), settings.max_clients);
+    // Отпечаток нужен клиентам, которые проверяют самоподписанный сертификат сервера по отпечатку
+    let fingerprint = tls.as_ref().map(|(_, fingerprint)| fingerprint.clone());
+    if let Some(fingerprint) = &fingerprint {
+        println!("TLS включён, отпечаток сертификата SHA-256: {}", fingerprint);
+    }
+    let tls = tls.map(|(acceptor, _)| acceptor);
+
+    // Журнал записывается отдельным потоком; если файл недоступен, записи выводятся в stderr
+    let logging = Logging::init(&config.log.file, config.log.format, &config.log.level, &config.log.rotation)
+        .or_else(|e| {
+            eprintln!("Ошибка журнала: {}; записи выводятся в stderr", e);
+            Logging::stderr(config.log.format, &config.log.level)
+        })
+        .map_err(ServerError::Logging)?;
+    #[cfg(unix)]
+    spawn_log_reload::<S>(spec, cli, logging.filter());
+
+    info!(server = spec.name, ?listen, tls = fingerprint.is_some(), auth = auth.enabled(), access = access.enabled(), "Сервер запущен");
+    if let Some(fingerprint) = fingerprint {
+        info!(fingerprint = %fingerprint, "TLS включён");
+    }
+    if auth.accepts_tokens() && tls.is_none() {
+        warn!("Токены клиентов передаются без шифрования: TLS не настроен (tls.cert_file)");
+    }
+    for service in &services {
+        for provider in &service.providers {
+            provider.start();
+        }
+    }
+
+    let clients = Arc::new(Semaphore::new(settings.max_clients)); // Свободные места для клиентов
+    let rejects = Arc::new(Semaphore::new(MAX_PENDING_REJECTS)); // Свободные места для отказов
+
+    let (shutdown_sender, shutdown) = watch::channel(None); // Причина остановки для обработчиков клиентов
+    let signal = shutdown_signal();
+    tokio::pin!(signal);
+
+    let reason = loop { // Обработка входящих соединений: по задаче на клиента
+        let accepted = tokio::select! {
+            reason = &mut signal => break reason,
+            accepted = accept_any(&listeners) => accepted,
+        };
+        match accepted {
+            Ok((index, stream, client_addr)) => {
+                // Записи журнала об обработке клиента содержат его адрес, идентификатор и имя опознанного клиента
+                let span = tracing::info_span!(
+                    "client",
+                    peer = %client_addr,
+                    client_id = tracing::field::Empty,
+                    identity = tracing::field::Empty
+                );
+                // Запрещённый адрес и превышение частоты подключений не занимают места в пределе клиентов
+                if let Err(rejection) = access.admit(client_addr.ip()) {
+                    span.in_scope(|| warn!(reason = %rejection.reason, retry_after_ms = rejection.retry_after_ms(), "Подключение отклонено"));
+                    spawn_reject(&rejects, stream, settings, tls.clone(), rejection.response(), span);
+                    continue;
+                }
+                let Ok(permit) = Arc::clone(&clients).try_acquire_owned() else {
+                    span.in_scope(|| warn!(max_clients = settings.max_clients, "Подключение отклонено: обслуживается максимум клиентов"));
+                    let busy = Response::Busy { retry_after_ms: BUSY_RETRY_AFTER_MS };
+                    spawn_reject(&rejects, stream, settings, tls.clone(), busy, span);
+                    continue;
+                };
+                let providers = Arc::clone(&routes[index]);
+                let client = access.client(client_addr.ip());
+                let session = handle_client(stream, permit, settings, shutdown.clone(), providers, tls.clone(), Arc::clone(&auth), client);
+                tokio::spawn(session.instrument(span));
+            }
+            Err(e) => {
+                error!(error = %e, "Ошибка подключения");
+                println!("Ошибка подключения: {}", e);
+                sleep(ACCEPT_RETRY_DELAY).await;
+            }
+        }
+    };
+
+    // Остановка: новые подключения не принимаются, подключенные клиенты получают уведомление
+    drop(listeners);
+    info!(reason = %reason, "Остановка сервера");
+    shutdown_sender.send_replace(Some(reason));
+
+    // Обработчики освобождают места в пределе клиентов по завершении
+    let all_clients = settings.max_clients as u32; // Предел не больше u32::MAX: проверено в настройках
+    let shutdown_grace = Duration::from_millis(config.timeouts.shutdown_grace_ms);
+    if timeout(shutdown_grace, clients.acquire_many(all_clients)).await.is_err() {
+        warn!(grace_ms = config.timeouts.shutdown_grace_ms, "Не все клиенты отключились до остановки");
+    }
+
+    info!("Сервер остановлен");
+    drop(logging); // Запись накопленных сообщений журнала
+    println!("{} остановлен", spec.title);
+    Ok(())
+}
+
+// Отказ клиенту в отдельной задаче. Если одновременно отправляется предельное число отказов,
+// соединение закрывается сразу, без рукопожатия TLS и ответа: поток подключений с запрещённого
+// адреса или сверх предела клиентов не должен порождать неограниченное число задач
+fn spawn_reject(
+    rejects: &Arc<Semaphore>,
+    stream: TcpStream,
+    settings: ConnectionSettings,
+    tls: Option<TlsAcceptor>,
+    response: Response,
+    span: Span,
+) {
+    match Arc::clone(rejects).try_acquire_owned() {
+        Ok(permit) => {
+            let rejected = async move {
+                reject(stream, settings, tls, response).await;
+                drop(permit);
+            };
+            tokio::spawn(rejected.instrument(span));
+        }
+        Err(_) => span.in_scope(|| debug!("Отправляется слишком много отказов, соединение закрыто без ответа")),
+    }
+}
+
+// SIGHUP: фильтр уровней журнала перечитывается из настроек без перезапуска сервера
+#[cfg(unix)]
+fn spawn_log_reload<S: ProviderSettings>(spec: &'static ServerSpec, cli: Cli<S::Args>, filter: logging::LogFilter) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let Ok(mut hangup) = signal(SignalKind::hangup()) else {
+        warn!("Не удалось установить обработчик SIGHUP");
+        return;
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            let reloaded = Config::<S>::load(&cli, spec)
+                .and_then(|config| filter.set(&config.log.level).map(|()| config.log.level));
+            match reloaded {
+                Ok(level) => info!(level = %level, "Фильтр журнала обновлён"),
+                Err(e) => error!(error = %e, "Не удалось обновить фильтр журнала"),
+            }
+        }
+    });
+}
+
+// Ожидание сигнала остановки сервера; возвращается причина, сообщаемая клиентам
+async fn shutdown_signal() -> String {
+    let interrupt = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "сервер остановлен (Ctrl+C)".to_string(),
+            Err(_) => std::future::pending().await, // Обработчик не установлен: остановка только по SIGTERM
+        }
+    };
+    #[cfg(unix)]
+    if let Ok(mut terminate) = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+        return tokio::select! {
+            reason = interrupt => reason,
+            _ = terminate.recv() => "сервер остановлен (SIGTERM)".to_string(),
+        };
+    }
+    interrupt.await
+}
@@ -0,0 +1,461 @@
+// Сессия клиента: приветствие, запросы, рассылка по подписке и события источника
+use std::sync::Arc;
+use std::time::Duration;
+
+use protocol::tls::{certificate_digest, FINGERPRINT_SIZE};
+use protocol::{read_message_async, write_message_async, FrameError, Request, Response, ServerKind, PROTOCOL_VERSION};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+use tokio::time::{sleep_until, timeout, Instant};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+use crate::access::ClientAccess;
+use crate::auth::Authenticator;
+use crate::DataProvider;
+
+// Число прочитанных, но ещё не обработанных запросов одного клиента
+const REQUEST_QUEUE_SIZE: usize = 16;
+// Сколько отклонённое соединение ждёт закрытия клиентом, прежде чем будет сброшено
+const REJECT_LINGER: Duration = Duration::from_secs(1);
+
+// Результат чтения очередного кадра с запросом
+type IncomingRequest = Result<Option<Request>, FrameError>;
+
+// Соединение с клиентом: TCP или TLS поверх TCP
+trait ClientStream: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> ClientStream for T {}
+
+// Отпечаток сертификата, предъявленного клиентом при рукопожатии TLS
+type ClientCertificate = [u8; FINGERPRINT_SIZE];
+
+type Reader = ReadHalf<Box<dyn ClientStream>>;
+type Writer = WriteHalf<Box<dyn ClientStream>>;
+
+// Параметры подключений
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectionSettings {
+    pub max_clients: usize,      // Предел одновременно обслуживаемых клиентов
+    pub read_timeout: Duration,  // Молчание клиента, после которого он считается отключившимся
+    pub write_timeout: Duration, // Предельная длительность отправки одного ответа
+    pub keepalive: Duration,     // Простой соединения до первой проверки TCP keepalive
+    pub min_interval_ms: u64,    // Допустимые границы интервала рассылки по подписке
+    pub max_interval_ms: u64,
+}
+
+// Запись ответа клиенту с ограничением длительности
+async fn write_response(writer: &mut Writer, response: &Response, write_timeout: Duration) -> Result<usize, FrameError> {
+    match timeout(write_timeout, write_message_async(writer, response)).await {
+        Ok(result) => result,
+        Err(_) => Err(FrameError::Io(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("клиент не принял данные за {} мс", write_timeout.as_millis()),
+        ))),
+    }
+}
+
+// Отправка ответа клиенту с проверкой соединения; false - соединение потеряно
+async fn send_response(
+    writer: &mut Writer,
+    response: &Response,
+    write_timeout: Duration,
+) -> bool {
+    match write_response(writer, response, write_timeout).await {
+        Ok(bytes) => {
+            debug!(bytes, ?response, "Данные отправлены клиенту");
+            true
+        }
+        // Ответ не сериализован или не помещается в кадр: в соединение ничего не записано,
+        // поэтому клиенту вместо ответа сообщается об ошибке и соединение продолжается
+        Err(e @ (FrameError::TooLarge(_) | FrameError::Encode(_))) => {
+            warn!(error = %e, "Ответ не может быть отправлен клиенту");
+            let error = Response::Error { message: format!("Ответ сервера не может быть отправлен: {}", e) };
+            match write_response(writer, &error, write_timeout).await {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!(error = %e, "Ошибка отправки данных клиенту");
+                    false
+                }
+            }
+        }
+        Err(e) => {
+            warn!(error = %e, "Ошибка отправки данных клиенту");
+            false
+        }
+    }
+}
+
+// Приветствие: первым кадром клиент обязан прислать Hello с совместимой версией,
+// при проверке подлинности - с токеном, если клиент не предъявил сертификат TLS.
+// Возвращается номер источника, выбранного по типу сервера из приветствия
+async fn handshake(
+    reader: &mut Reader,
+    writer: &mut Writer,
+    providers: &[Arc<dyn DataProvider>],
+    auth: &Authenticator,
+    certificate: Option<&ClientCertificate>,
+    access: &mut ClientAccess,
+) -> Option<usize> {
+    let reply = match read_message_async::<_, Request>(reader).await {
+        Ok(Some(Request::Hello { version, client_id, server_kind, token })) if version == PROTOCOL_VERSION => {
+            tracing::Span::current().record("client_id", client_id);
+            info!(version, ?server_kind, "Приветствие от клиента");
+            match auth.authenticate(token.as_ref(), certificate) {
+                Ok(identity) => {
+                    if let Some(identity) = &identity {
+                        tracing::Span::current().record("identity", identity.as_str());
+                        info!("Клиент опознан");
+                    }
+                    let admitted = match &identity {
+                        Some(identity) => access.identify(identity),
+                        None => Ok(()),
+                    };
+                    if let Err(rejection) = admitted {
+                        warn!(retry_after_ms = rejection.retry_after_ms(), "Приветствие отклонено: превышена частота подключений клиента");
+                        return answer_hello(writer, Err(rejection.response())).await;
+                    }
+                    select_provider(providers, server_kind).map_err(error_response).map(|selected| {
+                        let server_kind = providers[selected].kind();
+                        let capabilities = capabilities(providers);
+                        (Response::Hello { server_kind, version: PROTOCOL_VERSION, capabilities, identity }, selected)
+                    })
+                }
+                Err(message) => {
+                    warn!(reason = %message, "Клиент не прошёл проверку подлинности");
+                    Err(Response::Unauthorized { message })
+                }
+            }
+        }
+        Ok(Some(Request::Hello { version, client_id, .. })) => {
+            tracing::Span::current().record("client_id", client_id);
+            Err(error_response(format!("Несовместимая версия протокола клиента: {}, поддерживается {}", version, PROTOCOL_VERSION)))
+        }
+        Ok(Some(other)) => Err(error_response(format!("Ожидалось приветствие, получено: {:?}", other))),
+        Ok(None) => Err(error_response("Соединение закрыто до приветствия".to_string())),
+        Err(e) => Err(error_response(format!("Ошибка чтения приветствия: {}", e))),
+    };
+
+    answer_hello(writer, reply).await
+}
+
+// Ответ на приветствие; ошибка - отказ, после которого соединение закрывается
+async fn answer_hello(writer: &mut Writer, reply: Result<(Response, usize), Response>) -> Option<usize> {
+    let (response, selected) = match reply {
+        Ok((response, selected)) => (response, Some(selected)),
+        Err(response) => (response, None),
+    };
+
+    if let Err(e) = write_message_async(writer, &response).await {
+        warn!(error = %e, "Ошибка отправки приветствия");
+        return None;
+    }
+    selected
+}
+
+// Отказ в приветствии с записью причины в журнал
+fn error_response(message: String) -> Response {
+    warn!("{}", message);
+    Response::Error { message }
+}
+
+// Возможности всех источников адреса: остальным передаются запросы, которых не знает выбранный
+fn capabilities(providers: &[Arc<dyn DataProvider>]) -> Vec<String> {
+    let mut capabilities = vec!["get".to_string(), "subscribe".to_string()];
+    for provider in providers {
+        for capability in provider.capabilities() {
+            if !capabilities.contains(&capability) {
+                capabilities.push(capability);
+            }
+        }
+    }
+    capabilities
+}
+
+// Источник для клиента: указанного в приветствии типа или первый, если тип не указан
+fn select_provider(providers: &[Arc<dyn DataProvider>], kind: Option<ServerKind>) -> Result<usize, String> {
+    let Some(kind) = kind else { return Ok(0) };
+    providers.iter().position(|provider| provider.kind() == kind).ok_or_else(|| {
+        let offered: Vec<String> = providers.iter().map(|provider| format!("\"{}\"", provider.kind())).collect();
+        format!("Сервер \"{}\" недоступен на этом адресе, доступны: {}", kind, offered.join(", "))
+    })
+}
+
+// Чтение запросов клиента в отдельной задаче: прерывать чтение кадра на середине нельзя,
+// а основной цикл ждёт одновременно запросы, события источника и момент рассылки
+async fn read_requests(mut reader: Reader, requests: tokio::sync::mpsc::Sender<IncomingRequest>) {
+    loop {
+        let request = read_message_async(&mut reader).await;
+        // После некорректного кадра соединение продолжается, после закрытия или ошибки - нет
+        let finished = !matches!(request, Ok(Some(_)) | Err(FrameError::Decode(_)));
+        if requests.send(request).await.is_err() || finished {
+            return;
+        }
+    }
+}
+
+// Очередное событие источника; без подписки ожидание никогда не завершается
+async fn next_event(events: &mut Option<UnboundedReceiver<Response>>) -> Option<Response> {
+    match events {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+// Установка TLS-соединения, если TLS настроен; рукопожатие ограничено таймаутом чтения.
+// Возвращается и отпечаток сертификата клиента, если клиент его предъявил
+async fn open_stream(
+    stream: TcpStream,
+    tls: Option<TlsAcceptor>,
+    handshake_timeout: Duration,
+) -> std::io::Result<(Box<dyn ClientStream>, Option<ClientCertificate>)> {
+    let Some(acceptor) = tls else { return Ok((Box::new(stream), None)) };
+    match timeout(handshake_timeout, acceptor.accept(stream)).await {
+        Ok(stream) => {
+            let stream = stream?;
+            let certificate = stream.get_ref().1.peer_certificates().and_then(|chain| chain.first()).map(certificate_digest);
+            Ok((Box::new(stream), certificate))
+        }
+        Err(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("рукопожатие TLS не завершено за {} мс", handshake_timeout.as_millis()),
+        )),
+    }
+}
+
+// Обработка клиентского подключения
+// (выполняется в области журнала с адресом и идентификатором клиента)
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_client(
+    stream: TcpStream,
+    _permit: OwnedSemaphorePermit, // Место в пределе клиентов, освобождается по завершении обработки
+    settings: ConnectionSettings,
+    shutdown: watch::Receiver<Option<String>>,
+    providers: Arc<[Arc<dyn DataProvider>]>, // Источники данных адреса, на котором принято подключение
+    tls: Option<TlsAcceptor>,
+    auth: Arc<Authenticator>,
+    mut access: ClientAccess, // Ограничение частоты запросов клиента
+) {
+    info!("Клиент подключен");
+    if let Err(e) = enable_keepalive(&stream, settings.keepalive) {
+        warn!(error = %e, "Не удалось включить TCP keepalive");
+    }
+    let (stream, certificate) = match open_stream(stream, tls, settings.read_timeout).await {
+        Ok(opened) => opened,
+        Err(e) => {
+            warn!(error = %e, "Ошибка установки TLS-соединения");
+            return;
+        }
+    };
+
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let handshake = handshake(&mut reader, &mut writer, &providers, &auth, certificate.as_ref(), &mut access);
+    let selected = match timeout(settings.read_timeout, handshake).await {
+        Ok(selected) => selected,
+        Err(_) => {
+            warn!(timeout_ms = settings.read_timeout.as_millis() as u64, "Клиент не прислал приветствие");
+            None
+        }
+    };
+    let Some(selected) = selected else {
+        let _ = writer.shutdown().await;
+        return;
+    };
+    // Выбранный источник - первый: он отвечает на Get и подписку и первым получает прочие запросы
+    let mut providers = providers.to_vec();
+    providers.swap(0, selected);
+
+    let (request_sender, mut requests) = tokio::sync::mpsc::channel(REQUEST_QUEUE_SIZE);
+    let reader_task = tokio::spawn(read_requests(reader, request_sender));
+    serve_client(&mut writer, &mut requests, settings, shutdown, &providers, &access).await;
+    reader_task.abort();
+    if let Err(e) = writer.shutdown().await { // Закрываем соединение
+        debug!(error = %e, "Ошибка при отключении клиента");
+    }
+}
+
+// Обработка запросов и рассылка по подписке до отключения клиента
+async fn serve_client(
+    writer: &mut Writer,
+    requests: &mut Receiver<IncomingRequest>,
+    settings: ConnectionSettings,
+    mut shutdown: watch::Receiver<Option<String>>,
+    providers: &[Arc<dyn DataProvider>],
+    access: &ClientAccess,
+) {
+    let provider = &providers[0];
+    let mut subscription: Option<Duration> = None; // Интервал рассылки при активной подписке
+    let mut events: Option<UnboundedReceiver<Response>> = None; // События источника для подписчика
+    let mut next_push = Instant::now();
+    let mut last_seen = Instant::now(); // Время последнего кадра от клиента
+
+    loop {
+        let request = tokio::select! {
+            request = requests.recv() => request,
+            // Сервер останавливается: клиент уведомляется, соединение закрывается
+            Ok(()) = shutdown.changed() => {
+                let reason = shutdown.borrow().clone().unwrap_or_default();
+                send_response(writer, &Response::ServerShutdown { reason }, settings.write_timeout).await;
+                return;
+            }
+            // Клиент молчит дольше допустимого: соединение считается оборванным
+            _ = sleep_until(last_seen + settings.read_timeout) => {
+                warn!(timeout_ms = settings.read_timeout.as_millis() as u64, "Клиент не отвечает, соединение закрыто");
+                return;
+            }
+            // Пересылка события источника и обновлённых данных сразу после него
+            Some(event) = next_event(&mut events) => {
+                if !send_response(writer, &event, settings.write_timeout).await {
+                    return;
+                }
+                next_push = Instant::now();
+                continue;
+            }
+            _ = sleep_until(next_push), if subscription.is_some() => {
+                next_push = Instant::now() + subscription.unwrap_or_default();
+                if !send_response(writer, &snapshot(provider).await, settings.write_timeout).await {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        // Задача чтения завершается только после передачи закрытия соединения или ошибки
+        let Some(request) = request else { return };
+        last_seen = Instant::now();
+        // Частота проверяется до обработки: превысивший её клиент получает отказ и отключается
+        if let Ok(Some(_)) | Err(FrameError::Decode(_)) = &request {
+            if let Err(rejection) = access.request() {
+                warn!(retry_after_ms = rejection.retry_after_ms(), "Клиент отключён: превышена частота запросов");
+                send_response(writer, &rejection.response(), settings.write_timeout).await;
+                return;
+            }
+        }
+        let response = match request {
+            Ok(Some(Request::Disconnect)) => { // Проверяем, не запрос ли это на отключение
+                info!("Клиент отключился");
+                return;
+            }
+            Ok(Some(Request::Get)) => snapshot(provider).await,
+            Ok(Some(Request::Subscribe { interval_ms })) => {
+                let interval_ms = interval_ms.clamp(settings.min_interval_ms, settings.max_interval_ms);
+                subscription = Some(Duration::from_millis(interval_ms));
+                if events.is_none() {
+                    events = provider.events();
+                }
+                next_push = Instant::now(); // Первая рассылка сразу после подтверждения
+                info!(interval_ms, "Клиент подписался");
+                Response::Subscribed { interval_ms }
+            }
+            Ok(Some(Request::Unsubscribe)) => {
+                subscription = None;
+                events = None;
+                info!("Клиент отменил подписку");
+                Response::Unsubscribed
+            }
+            Ok(Some(Request::Ping)) => Response::Pong,
+            Ok(Some(Request::Hello { .. })) => {
+                Response::Error { message: "Повторное приветствие не допускается".to_string() }
+            }
+            Ok(Some(request)) => handle_request(providers, request).await,
+            Ok(None) => {
+                // Соединение было закрыто клиентом
+                info!("Соединение закрыто клиентом");
+                return;
+            }
+            Err(FrameError::Decode(e)) => {
+                // Кадр прочитан целиком, поэтому соединение можно продолжать
+                warn!(error = %e, "Некорректный запрос");
+                Response::Error { message: format!("Некорректный запрос: {}", e) }
+            }
+            Err(e) => {
+                warn!(error = %e, "Ошибка чтения запроса");
+                return;
+            }
+        };
+
+        if !send_response(writer, &response, settings.write_timeout).await {
+            return;
+        }
+    }
+}
+
+// Обращения к источнику данных могут читать файлы (/proc/bus/input/devices, поиск процессов в /proc),
+// поэтому выполняются в потоках для блокирующих операций, а не в рабочих потоках tokio.
+// Ошибка - ответ клиенту, если обработка завершилась аварийно
+async fn run_blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Result<T, Response> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| {
+        warn!(error = %e, "Ошибка обработки запроса");
+        Response::Error { message: "Внутренняя ошибка сервера при обработке запроса".to_string() }
+    })
+}
+
+// Текущие данные источника: ответ на Get и очередная рассылка
+async fn snapshot(provider: &Arc<dyn DataProvider>) -> Response {
+    let provider = Arc::clone(provider);
+    run_blocking(move || provider.snapshot()).await.unwrap_or_else(|error| error)
+}
+
+// Запрос, специфичный для источника данных: отвечает первый поддерживающий его источник
+async fn handle_request(providers: &[Arc<dyn DataProvider>], request: Request) -> Response {
+    let kind = providers[0].kind();
+    let providers = providers.to_vec();
+    let handled = run_blocking(move || providers.iter().find_map(|provider| provider.handle(&request)).ok_or(request));
+    match handled.await {
+        Ok(Ok(response)) => response,
+        Ok(Err(request)) => Response::Error {
+            message: format!("Запрос не поддерживается сервером \"{}\": {:?}", kind, request),
+        },
+        Err(error) => error,
+    }
+}
+
+// Включение TCP keepalive: ядро обнаружит оборванное соединение, даже если приложение молчит
+fn enable_keepalive(stream: &TcpStream, keepalive: Duration) -> std::io::Result<()> {
+    let params = TcpKeepalive::new().with_time(keepalive).with_interval(keepalive / 3);
+    SockRef::from(stream).set_tcp_keepalive(&params)
+}
+
+// Отказ клиенту до приветствия (предел клиентов, запрещённый адрес, частота подключений):
+// ответ и закрытие соединения. Непрочитанное приветствие клиента вычитывается,
+// иначе закрытие сбросит соединение до доставки ответа
+pub async fn reject(stream: TcpStream, settings: ConnectionSettings, tls: Option<TlsAcceptor>, response: Response) {
+    let stream = match open_stream(stream, tls, settings.read_timeout).await {
+        Ok((stream, _)) => stream,
+        Err(e) => {
+            debug!(error = %e, "Ошибка установки TLS-соединения с отклонённым клиентом");
+            return;
+        }
+    };
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    if !send_response(&mut writer, &response, settings.write_timeout).await {
+        return;
+    }
+    let _ = writer.shutdown().await;
+    let _ = timeout(REJECT_LINGER, tokio::io::copy(&mut reader, &mut tokio::io::sink())).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::MAX_FRAME_SIZE;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn oversized_response_is_replaced_by_error() {
+        let (server, mut client) = tokio::io::duplex(4 * MAX_FRAME_SIZE);
+        let (_reader, mut writer) = tokio::io::split(Box::new(server) as Box<dyn ClientStream>);
+        let oversized = Response::Error { message: "x".repeat(MAX_FRAME_SIZE) };
+
+        // Соединение остаётся рабочим: клиент получает ошибку, а затем следующий ответ
+        assert!(send_response(&mut writer, &oversized, Duration::from_secs(1)).await);
+        assert!(send_response(&mut writer, &Response::Pong, Duration::from_secs(1)).await);
+        match read_message_async::<_, Response>(&mut client).await.unwrap() {
+            Some(Response::Error { message }) => assert!(message.contains("превышает максимум"), "{}", message),
+            other => panic!("Ожидалась ошибка, получено: {:?}", other),
+        }
+        assert_eq!(read_message_async::<_, Response>(&mut client).await.unwrap(), Some(Response::Pong));
+    }
+}
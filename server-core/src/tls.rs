@@ -0,0 +1,19 @@
+// TLS на адресах сервера: настройки из файлов сертификата и ключа
+use std::sync::Arc;
+
+use protocol::tls::{fingerprint, load_certificates, load_private_key, server_config, TlsError};
+use tokio_rustls::TlsAcceptor;
+
+use crate::config::TlsConfig;
+
+// Приём TLS-соединений и отпечаток сертификата сервера для настройки клиентов;
+// None - TLS не настроен. При client_auth у клиентов запрашивается сертификат
+pub fn acceptor(config: &TlsConfig, client_auth: bool) -> Result<Option<(TlsAcceptor, String)>, TlsError> {
+    let (Some(cert_file), Some(key_file)) = (&config.cert_file, &config.key_file) else {
+        return Ok(None);
+    };
+    let certificates = load_certificates(cert_file)?;
+    let fingerprint = fingerprint(&certificates[0]);
+    let config = server_config(certificates, load_private_key(key_file)?, client_auth)?;
+    Ok(Some((TlsAcceptor::from(Arc::new(config)), fingerprint)))
+}
@@ -0,0 +1,65 @@
+// Настройки источника сведений о мыши: таблица [mouse] файла настроек и ключи командной строки.
+// Общие настройки сервера (адреса, таймауты, журнал) задаются в server-core
+use clap::{Args, ValueEnum};
+use serde::de::MapAccess;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize};
+use server_core::ProviderSettings;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MouseConfig {
+    pub provider: MouseProvider,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fake_script: Option<String>, // Сценарий для источника fake, например "3:1,5:0"
+}
+
+// Источник сведений о мыши
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseProvider {
+    #[default]
+    System, // Системный источник текущей ОС
+    Fake,   // Сценарный источник для тестов
+}
+
+// Ключи командной строки (переменные окружения SERVER1_MOUSE_PROVIDER и SERVER1_FAKE_MOUSE)
+#[derive(Debug, Clone, Args)]
+pub struct MouseArgs {
+    #[arg(long, value_name = "KIND", help = "Источник сведений о мыши")]
+    mouse_provider: Option<MouseProvider>,
+
+    #[arg(long, value_name = "SCRIPT",
+          help = "Сценарий для источника fake: шаги \"кнопки:колесико\" через запятую, например 3:1,5:0")]
+    fake_mouse: Option<String>,
+}
+
+impl ProviderSettings for MouseConfig {
+    const SECTIONS: &'static [&'static str] = &["mouse"];
+    type Args = MouseArgs;
+
+    fn apply(&mut self, args: &MouseArgs) {
+        if let Some(provider) = args.mouse_provider {
+            self.provider = provider;
+        }
+        if let Some(script) = &args.fake_mouse {
+            self.fake_script = Some(script.clone());
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.provider == MouseProvider::Fake && self.fake_script.is_none() {
+            return Err("mouse.fake_script: источник fake требует сценарий".to_string());
+        }
+        Ok(())
+    }
+
+    fn read_section<'de, M: MapAccess<'de>>(&mut self, _name: &str, map: &mut M) -> Result<(), M::Error> {
+        *self = map.next_value()?;
+        Ok(())
+    }
+
+    fn write_sections<M: SerializeMap>(&self, map: &mut M) -> Result<(), M::Error> {
+        map.serialize_entry("mouse", self)
+    }
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use chrono::Local;
+use protocol::{PointingDevice, Response};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{info, warn};
+
+use crate::mouse::MouseInfoProvider;
+
+// Рассылка событий подключения устройств всем подписанным клиентам
+#[derive(Default)]
+pub struct HotplugHub {
+    subscribers: Mutex<Vec<UnboundedSender<Response>>>,
+}
+
+impl HotplugHub {
+    // Регистрация подписчика; подписка снимается удалением приёмника. Отключившиеся подписчики
+    // удаляются и здесь: без событий устройств список иначе рос бы с каждой подпиской
+    pub fn subscribe(&self) -> UnboundedReceiver<Response> {
+        let (sender, receiver) = unbounded_channel();
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|subscriber| !subscriber.is_closed());
+        subscribers.push(sender);
+        receiver
+    }
+
+    // Отправка события; отключившиеся подписчики удаляются из списка
+    fn broadcast(&self, event: &Response) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+// Поток отслеживания подключения и отключения указывающих устройств
+pub fn spawn_watcher(provider: Arc<dyn MouseInfoProvider>, hub: Arc<HotplugHub>) -> io::Result<()> {
+    let watcher = thread::Builder::new().name("hotplug".to_string()); // Имя потока попадает в журнал
+    watcher.spawn(move || {
+        let mut known = provider.devices().unwrap_or_default();
+        loop {
+            provider.wait_for_change();
+
+            let current = match provider.devices() {
+                Ok(devices) => devices,
+                Err(e) => {
+                    warn!(error = %e, "Ошибка получения списка устройств");
+                    continue;
+                }
+            };
+
+            let timestamp = Local::now().timestamp();
+            for device in difference(&known, &current) {
+                info!(device = %device.name, bus = %device.bus, "Устройство отключено");
+                hub.broadcast(&Response::DeviceRemoved { device, timestamp });
+            }
+            for device in difference(&current, &known) {
+                info!(device = %device.name, bus = %device.bus, "Устройство подключено");
+                hub.broadcast(&Response::DeviceAdded { device, timestamp });
+            }
+            known = current;
+        }
+    })?;
+    Ok(())
+}
+
+// Устройства из first, которых нет в second (с учётом одинаковых устройств)
+fn difference(first: &[PointingDevice], second: &[PointingDevice]) -> Vec<PointingDevice> {
+    let mut unmatched: Vec<&PointingDevice> = second.iter().collect();
+    first
+        .iter()
+        .filter(|device| match unmatched.iter().position(|other| other == device) {
+            Some(index) => {
+                unmatched.swap_remove(index);
+                false
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closed_subscriptions_are_dropped_on_subscribe() {
+        let hub = HotplugHub::default();
+        for _ in 0..10 {
+            drop(hub.subscribe());
+        }
+        let _active = hub.subscribe();
+        assert_eq!(hub.subscribers.lock().unwrap().len(), 1);
+    }
+}
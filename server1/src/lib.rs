@@ -0,0 +1,71 @@
+// Сервер 1: источник сведений о мыши; используется программой server1 и общим сервером
+mod config;
+mod hotplug;
+mod mouse;
+
+pub use config::{MouseArgs, MouseConfig, MouseProvider};
+use hotplug::HotplugHub;
+use mouse::MouseInfoProvider;
+
+use std::sync::Arc;
+use chrono::Local;
+use protocol::{MouseInfo, Response, ServerKind};
+use server_core::{DataProvider, ServerSpec};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tracing::error;
+
+pub static SPEC: ServerSpec = ServerSpec {
+    name: "server1",
+    title: "Сервер 1",
+    about: "Сервер 1: сведения о мыши и указывающих устройствах",
+    version: env!("CARGO_PKG_VERSION"),
+    env_prefix: "SERVER1",
+    default_port: 7878,
+    default_log_file: "server_log.txt",
+};
+
+// Данные сервера 1: сведения о мыши и события подключения устройств
+struct MouseData {
+    provider: Arc<dyn MouseInfoProvider>,
+    hotplug: Arc<HotplugHub>,
+}
+
+impl DataProvider for MouseData {
+    fn kind(&self) -> ServerKind {
+        ServerKind::MouseInfo
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec!["hotplug".to_string()]
+    }
+
+    // Получение информации о мыши
+    fn snapshot(&self) -> Response {
+        match self.provider.query() {
+            Ok(metrics) => Response::MouseInfo(MouseInfo {
+                mouse_buttons: metrics.buttons,
+                has_scroll_wheel: metrics.has_scroll_wheel,
+                devices: metrics.devices,
+                timestamp: Local::now().timestamp(),
+            }),
+            Err(e) => Response::Error { message: format!("Не удалось получить информацию о мыши: {}", e) },
+        }
+    }
+
+    fn events(&self) -> Option<UnboundedReceiver<Response>> {
+        Some(self.hotplug.subscribe())
+    }
+
+    // Отслеживание подключения устройств; без него сервер продолжает отвечать на запросы
+    fn start(&self) {
+        if let Err(e) = hotplug::spawn_watcher(Arc::clone(&self.provider), Arc::clone(&self.hotplug)) {
+            error!(error = %e, "Не удалось запустить поток отслеживания устройств");
+        }
+    }
+}
+
+// Источник сведений о мыши выбирается при запуске (системный или сценарный)
+pub fn mouse_data(config: &MouseConfig) -> Result<Arc<dyn DataProvider>, String> {
+    let provider = mouse::provider_from_config(config)?;
+    Ok(Arc::new(MouseData { provider, hotplug: Arc::new(HotplugHub::default()) }))
+}
@@ -0,0 +1,83 @@
+use std::io;
+use std::sync::{Mutex, PoisonError};
+
+use protocol::{BusType, DeviceKind, PointingDevice};
+
+use super::{MouseInfoProvider, MouseMetrics};
+
+// Сценарный источник: каждый запрос возвращает следующий шаг сценария,
+// после последнего шага значение больше не меняется.
+// Шаг с ненулевым числом кнопок описывает одну виртуальную мышь;
+// список устройств для отслеживания подключений соответствует последнему выданному шагу
+pub struct ScriptedMouse {
+    steps: Vec<MouseMetrics>,
+    position: Mutex<usize>,
+}
+
+impl ScriptedMouse {
+    pub fn new(steps: Vec<MouseMetrics>) -> Self {
+        ScriptedMouse { steps, position: Mutex::new(0) }
+    }
+
+    // Сценарий вида "3:1,5:0,0:0" - пары <кнопки>:<колесико 0|1> через запятую
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let steps = script
+            .split(',')
+            .map(|step| {
+                let (buttons, wheel) = step
+                    .trim()
+                    .split_once(':')
+                    .ok_or_else(|| format!("шаг \"{}\" должен иметь вид <кнопки>:<колесико>", step))?;
+                let buttons = buttons
+                    .parse()
+                    .map_err(|_| format!("некорректное число кнопок в шаге \"{}\"", step))?;
+                let has_scroll_wheel = match wheel {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(format!("колесико в шаге \"{}\" должно быть 0 или 1", step)),
+                };
+                Ok(scripted_step(buttons, has_scroll_wheel))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(ScriptedMouse::new(steps))
+    }
+}
+
+impl MouseInfoProvider for ScriptedMouse {
+    fn query(&self) -> io::Result<MouseMetrics> {
+        let mut position = self.position.lock().unwrap_or_else(PoisonError::into_inner);
+        let step = self
+            .steps
+            .get(*position)
+            .or(self.steps.last())
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "пустой сценарий"))?;
+        *position += 1;
+        Ok(step)
+    }
+
+    fn devices(&self) -> io::Result<Vec<PointingDevice>> {
+        let position = self.position.lock().unwrap_or_else(PoisonError::into_inner);
+        let current = position.saturating_sub(1).min(self.steps.len().saturating_sub(1));
+        Ok(self.steps.get(current).map(|step| step.devices.clone()).unwrap_or_default())
+    }
+}
+
+fn scripted_step(buttons: u32, has_scroll_wheel: bool) -> MouseMetrics {
+    let devices = if buttons > 0 {
+        vec![PointingDevice {
+            name: "Сценарная мышь".to_string(),
+            vendor_id: 0,
+            product_id: 0,
+            bus: BusType::Virtual,
+            buttons,
+            vertical_wheel: has_scroll_wheel,
+            horizontal_wheel: false,
+            kind: DeviceKind::Mouse,
+        }]
+    } else {
+        Vec::new()
+    };
+    MouseMetrics::from_devices(devices)
+}
@@ -0,0 +1,302 @@
+use std::fs;
+use std::io;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use inotify::{Inotify, WatchMask};
+
+use protocol::{BusType, DeviceKind, PointingDevice};
+
+use super::{MouseInfoProvider, MouseMetrics};
+
+// Список устройств ввода ядра Linux
+const DEVICES_PATH: &str = "/proc/bus/input/devices";
+// Каталог узлов устройств ввода, отслеживаемый через inotify
+const INPUT_DIR: &str = "/dev/input";
+// Пауза после события, чтобы объединить создание mouseN и eventN одного устройства
+const HOTPLUG_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+// Коды событий и кнопок из linux/input-event-codes.h
+const BTN_LEFT: usize = 0x110;
+const BTN_TASK: usize = 0x117; // Последняя кнопка мыши (BTN_LEFT..BTN_TASK)
+const REL_X: usize = 0x00;
+const REL_Y: usize = 0x01;
+const BTN_TOOL_FINGER: usize = 0x145; // Признак тачпада
+const REL_HWHEEL: usize = 0x06;
+const REL_WHEEL: usize = 0x08;
+const ABS_X: usize = 0x00;
+const ABS_Y: usize = 0x01;
+
+// Типы шин из linux/input.h
+const BUS_USB: u16 = 0x03;
+const BUS_BLUETOOTH: u16 = 0x05;
+const BUS_VIRTUAL: u16 = 0x06;
+const BUS_I8042: u16 = 0x11;
+const BUS_I2C: u16 = 0x18;
+
+// Битовая маска возможностей устройства из строки "B: KEY=..."
+#[derive(Debug, Default, Clone)]
+struct Bitmap {
+    words: Vec<u64>, // Младшее слово первым
+}
+
+impl Bitmap {
+    // Ядро печатает слова типа long в шестнадцатеричном виде, старшее слово первым
+    fn parse(value: &str) -> Self {
+        let words = value
+            .split_whitespace()
+            .rev()
+            .map(|word| u64::from_str_radix(word, 16).unwrap_or(0))
+            .collect();
+        Bitmap { words }
+    }
+
+    fn has(&self, bit: usize) -> bool {
+        let word_bits = usize::BITS as usize; // Размер long совпадает с разрядностью платформы
+        self.words
+            .get(bit / word_bits)
+            .is_some_and(|word| word & (1 << (bit % word_bits)) != 0)
+    }
+}
+
+// Описание одного устройства ввода из /proc/bus/input/devices
+#[derive(Debug, Default, Clone)]
+struct InputDevice {
+    name: String,
+    bus: u16,
+    vendor: u16,
+    product: u16,
+    key: Bitmap, // Кнопки и клавиши
+    rel: Bitmap, // Относительные оси (перемещение, колесо)
+    abs: Bitmap, // Абсолютные оси (тачпады)
+}
+
+impl InputDevice {
+    // Указывающее устройство: есть левая кнопка и оси перемещения
+    fn is_pointer(&self) -> bool {
+        self.key.has(BTN_LEFT)
+            && ((self.rel.has(REL_X) && self.rel.has(REL_Y)) || (self.abs.has(ABS_X) && self.abs.has(ABS_Y)))
+    }
+
+    fn button_count(&self) -> u32 {
+        (BTN_LEFT..=BTN_TASK).filter(|&bit| self.key.has(bit)).count() as u32
+    }
+
+    fn to_pointing_device(&self) -> PointingDevice {
+        let bus = match self.bus {
+            BUS_USB => BusType::Usb,
+            BUS_BLUETOOTH => BusType::Bluetooth,
+            BUS_I8042 => BusType::Ps2,
+            BUS_I2C => BusType::I2c,
+            BUS_VIRTUAL => BusType::Virtual,
+            _ => BusType::Other,
+        };
+        let kind = if self.key.has(BTN_TOOL_FINGER) && self.abs.has(ABS_X) {
+            DeviceKind::Touchpad
+        } else {
+            DeviceKind::Mouse
+        };
+
+        PointingDevice {
+            name: self.name.clone(),
+            vendor_id: self.vendor,
+            product_id: self.product,
+            bus,
+            buttons: self.button_count(),
+            vertical_wheel: self.rel.has(REL_WHEEL),
+            horizontal_wheel: self.rel.has(REL_HWHEEL),
+            kind,
+        }
+    }
+}
+
+// Разбор строки вида "I: Bus=0003 Vendor=046d Product=c077 Version=0111"
+fn parse_ids(device: &mut InputDevice, ids: &str) {
+    for field in ids.split_whitespace() {
+        let Some((key, value)) = field.split_once('=') else { continue };
+        let value = u16::from_str_radix(value, 16).unwrap_or(0);
+        match key {
+            "Bus" => device.bus = value,
+            "Vendor" => device.vendor = value,
+            "Product" => device.product = value,
+            _ => {}
+        }
+    }
+}
+
+// Разбор файла устройств: блоки разделены пустыми строками
+fn parse_devices(contents: &str) -> Vec<InputDevice> {
+    let mut devices = Vec::new();
+    let mut current = InputDevice::default();
+    let mut has_data = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            if has_data {
+                devices.push(std::mem::take(&mut current));
+                has_data = false;
+            }
+            continue;
+        }
+
+        has_data = true;
+        if let Some(ids) = line.strip_prefix("I: ") {
+            parse_ids(&mut current, ids);
+        } else if let Some(name) = line.strip_prefix("N: Name=") {
+            current.name = name.trim_matches('"').to_string();
+        } else if let Some(bits) = line.strip_prefix("B: KEY=") {
+            current.key = Bitmap::parse(bits);
+        } else if let Some(bits) = line.strip_prefix("B: REL=") {
+            current.rel = Bitmap::parse(bits);
+        } else if let Some(bits) = line.strip_prefix("B: ABS=") {
+            current.abs = Bitmap::parse(bits);
+        }
+    }
+    if has_data {
+        devices.push(current);
+    }
+
+    devices
+}
+
+// Источник сведений о мыши для Linux
+pub struct LinuxMouse {
+    watcher: Mutex<Option<Inotify>>, // None - inotify недоступен, используется опрос
+}
+
+impl LinuxMouse {
+    pub fn new() -> Self {
+        let watcher = Inotify::init().and_then(|inotify| {
+            inotify.watches().add(INPUT_DIR, WatchMask::CREATE | WatchMask::DELETE)?;
+            Ok(inotify)
+        });
+        LinuxMouse { watcher: Mutex::new(watcher.ok()) }
+    }
+}
+
+impl MouseInfoProvider for LinuxMouse {
+    fn query(&self) -> io::Result<MouseMetrics> {
+        let contents = fs::read_to_string(DEVICES_PATH)?;
+        let devices = parse_devices(&contents)
+            .iter()
+            .filter(|device| device.is_pointer())
+            .map(InputDevice::to_pointing_device)
+            .collect();
+
+        Ok(MouseMetrics::from_devices(devices))
+    }
+
+    fn wait_for_change(&self) {
+        let mut watcher = self.watcher.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(inotify) = watcher.as_mut() else {
+            drop(watcher);
+            thread::sleep(super::HOTPLUG_POLL_INTERVAL);
+            return;
+        };
+
+        let mut buffer = [0u8; 4096];
+        if inotify.read_events_blocking(&mut buffer).is_err() {
+            *watcher = None; // Дальше список устройств будет опрашиваться периодически
+            return;
+        }
+        drop(watcher);
+        thread::sleep(HOTPLUG_SETTLE_DELAY);
+    }
+}
+
+// Образцы из /proc/bus/input/devices 64-разрядной системы: слова масок по 64 бита
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    use super::*;
+
+    const DEVICES: &str = r#"I: Bus=0011 Vendor=0001 Product=0001 Version=ab41
+N: Name="AT Translated Set 2 keyboard"
+P: Phys=isa0060/serio0/input0
+S: Sysfs=/devices/platform/i8042/serio0/input/input0
+U: Uniq=
+H: Handlers=sysrq kbd event0 leds
+B: PROP=0
+B: EV=120013
+B: KEY=402000000 3803078f800d001 feffffdfffefffff fffffffffffffffe
+B: MSC=10
+B: LED=7
+
+I: Bus=0003 Vendor=046d Product=c077 Version=0111
+N: Name="Logitech USB Optical Mouse"
+P: Phys=usb-0000:00:14.0-1/input0
+S: Sysfs=/devices/pci0000:00/0000:00:14.0/usb1/1-1/1-1:1.0/0003:046D:C077.0001/input/input5
+U: Uniq=
+H: Handlers=mouse0 event3
+B: PROP=0
+B: EV=17
+B: KEY=ff0000 0 0 0 0
+B: REL=1943
+B: MSC=10
+
+I: Bus=0011 Vendor=0002 Product=0007 Version=01b1
+N: Name="SynPS/2 Synaptics TouchPad"
+P: Phys=isa0060/serio1/input0
+S: Sysfs=/devices/platform/i8042/serio1/input/input6
+U: Uniq=
+H: Handlers=mouse1 event6
+B: PROP=5
+B: EV=b
+B: KEY=e520 10000 0 0 0 0
+B: ABS=660800011000003
+"#;
+
+    fn devices() -> Vec<InputDevice> {
+        parse_devices(DEVICES)
+    }
+
+    #[test]
+    fn blocks_are_split_into_devices() {
+        let devices = devices();
+        let names: Vec<&str> = devices.iter().map(|device| device.name.as_str()).collect();
+        assert_eq!(names, ["AT Translated Set 2 keyboard", "Logitech USB Optical Mouse", "SynPS/2 Synaptics TouchPad"]);
+        assert_eq!((devices[1].bus, devices[1].vendor, devices[1].product), (BUS_USB, 0x046d, 0xc077));
+        // Лишние пустые строки и пробелы не создают пустых устройств
+        assert_eq!(parse_devices(&format!("\n\n{}\n\n\n", DEVICES.replace('\n', "  \n"))).len(), 3);
+        assert!(parse_devices("").is_empty());
+    }
+
+    #[test]
+    fn multi_word_bitmap_starts_with_most_significant_word() {
+        let key = &devices()[0].key;
+        assert_eq!(key.words.len(), 4);
+        assert!(!key.has(0)); // KEY_RESERVED
+        assert!(key.has(1)); // KEY_ESC
+        assert!(key.has(64)); // Младший бит второго слова
+        assert!(!key.has(84));
+        assert!(key.has(3 * 64 + 25));
+        assert!(!key.has(BTN_LEFT), "бит за пределами маски не установлен");
+        assert!(!Bitmap::parse("").has(0));
+        assert!(!Bitmap::parse("zz 1").has(64), "некорректное слово считается нулевым");
+    }
+
+    #[test]
+    fn only_mice_and_touchpads_are_pointers() {
+        let pointers: Vec<bool> = devices().iter().map(InputDevice::is_pointer).collect();
+        assert_eq!(pointers, [false, true, true]);
+    }
+
+    #[test]
+    fn mouse_capabilities_are_reported() {
+        let mouse = devices()[1].to_pointing_device();
+        assert_eq!(mouse.kind, DeviceKind::Mouse);
+        assert_eq!(mouse.bus, BusType::Usb);
+        assert_eq!(mouse.buttons, 8);
+        assert!(mouse.vertical_wheel && mouse.horizontal_wheel);
+    }
+
+    #[test]
+    fn touchpad_is_recognized_by_finger_tool() {
+        let touchpad = devices()[2].to_pointing_device();
+        assert_eq!(touchpad.kind, DeviceKind::Touchpad);
+        assert_eq!(touchpad.bus, BusType::Ps2);
+        assert_eq!(touchpad.buttons, 1);
+        assert!(!touchpad.vertical_wheel && !touchpad.horizontal_wheel);
+    }
+}
@@ -0,0 +1,88 @@
+// Источники сведений о мыши: системный (по целевой ОС) и сценарный для тестов
+mod fake;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(windows)]
+mod windows;
+
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use protocol::PointingDevice;
+
+use crate::config::{MouseConfig, MouseProvider};
+
+pub use fake::ScriptedMouse;
+#[cfg(target_os = "linux")]
+pub use linux::LinuxMouse;
+#[cfg(windows)]
+pub use windows::WindowsMouse;
+
+// Сведения о мыши: сводные (аналог GetSystemMetrics) и по каждому устройству
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseMetrics {
+    pub buttons: u32,                 // Количество кнопок мыши (0 - мышь не подключена)
+    pub has_scroll_wheel: bool,       // Наличие колесика мыши
+    pub devices: Vec<PointingDevice>, // Подключенные указывающие устройства
+}
+
+impl MouseMetrics {
+    // Сводка по списку устройств: максимальное число кнопок
+    // и наличие колесика хотя бы у одного из них
+    pub fn from_devices(devices: Vec<PointingDevice>) -> Self {
+        MouseMetrics {
+            buttons: devices.iter().map(|device| device.buttons).max().unwrap_or(0),
+            has_scroll_wheel: devices.iter().any(|device| device.vertical_wheel),
+            devices,
+        }
+    }
+}
+
+// Период опроса списка устройств, если источник не умеет ждать изменений
+pub const HOTPLUG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// Источник сведений о мыши, общий для всех потоков обработки клиентов
+pub trait MouseInfoProvider: Send + Sync {
+    fn query(&self) -> io::Result<MouseMetrics>;
+
+    // Текущий список устройств для отслеживания подключений
+    fn devices(&self) -> io::Result<Vec<PointingDevice>> {
+        self.query().map(|metrics| metrics.devices)
+    }
+
+    // Блокирующее ожидание возможного изменения набора устройств
+    fn wait_for_change(&self) {
+        thread::sleep(HOTPLUG_POLL_INTERVAL);
+    }
+}
+
+// Системный источник для текущей ОС
+#[cfg(target_os = "linux")]
+fn system_provider() -> Result<Arc<dyn MouseInfoProvider>, String> {
+    Ok(Arc::new(LinuxMouse::new()))
+}
+
+#[cfg(windows)]
+fn system_provider() -> Result<Arc<dyn MouseInfoProvider>, String> {
+    Ok(Arc::new(WindowsMouse))
+}
+
+// На остальных ОС сервер собирается и работает со сценарным источником
+#[cfg(not(any(target_os = "linux", windows)))]
+fn system_provider() -> Result<Arc<dyn MouseInfoProvider>, String> {
+    Err("системный источник не поддерживается на этой ОС".to_string())
+}
+
+// Выбор источника по настройкам сервера
+pub fn provider_from_config(config: &MouseConfig) -> Result<Arc<dyn MouseInfoProvider>, String> {
+    match config.provider {
+        MouseProvider::System => system_provider(),
+        MouseProvider::Fake => {
+            let script = config.fake_script.as_deref().unwrap_or_default();
+            let mouse = ScriptedMouse::parse(script).map_err(|e| format!("mouse.fake_script: {}", e))?;
+            Ok(Arc::new(mouse))
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::io;
+use std::mem;
+use std::ptr;
+
+use protocol::{BusType, DeviceKind, PointingDevice};
+use winapi::shared::minwindef::UINT;
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::{
+    GetRawInputDeviceInfoW, GetRawInputDeviceList, GetSystemMetrics, RAWINPUTDEVICELIST, RID_DEVICE_INFO,
+    RIDI_DEVICEINFO, RIDI_DEVICENAME, RIM_TYPEMOUSE, SM_CMOUSEBUTTONS, SM_MOUSEWHEELPRESENT,
+};
+
+use super::{MouseInfoProvider, MouseMetrics};
+
+// Источник сведений о мыши для Windows (GetSystemMetrics и Raw Input)
+pub struct WindowsMouse;
+
+impl MouseInfoProvider for WindowsMouse {
+    fn query(&self) -> io::Result<MouseMetrics> {
+        let mouse_buttons = unsafe { GetSystemMetrics(SM_CMOUSEBUTTONS) };
+        let has_scroll_wheel = unsafe { GetSystemMetrics(SM_MOUSEWHEELPRESENT) } != 0;
+
+        // Raw Input не сообщает о вертикальном колесе отдельных устройств,
+        // поэтому для них используется общий признак системы
+        let devices = raw_mouse_handles()?
+            .into_iter()
+            .filter_map(|handle| device_info(handle, has_scroll_wheel))
+            .collect();
+
+        Ok(MouseMetrics {
+            buttons: mouse_buttons.max(0) as u32,
+            has_scroll_wheel,
+            devices,
+        })
+    }
+}
+
+// Дескрипторы всех мышей, зарегистрированных в Raw Input
+fn raw_mouse_handles() -> io::Result<Vec<HANDLE>> {
+    let entry_size = mem::size_of::<RAWINPUTDEVICELIST>() as UINT;
+    let mut count: UINT = 0;
+    if unsafe { GetRawInputDeviceList(ptr::null_mut(), &mut count, entry_size) } == UINT::MAX {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut list: Vec<RAWINPUTDEVICELIST> = vec![unsafe { mem::zeroed() }; count as usize];
+    let written = unsafe { GetRawInputDeviceList(list.as_mut_ptr(), &mut count, entry_size) };
+    if written == UINT::MAX {
+        return Err(io::Error::last_os_error());
+    }
+    list.truncate(written as usize);
+
+    Ok(list
+        .into_iter()
+        .filter(|entry| entry.dwType == RIM_TYPEMOUSE)
+        .map(|entry| entry.hDevice)
+        .collect())
+}
+
+fn device_info(handle: HANDLE, vertical_wheel: bool) -> Option<PointingDevice> {
+    let mut info: RID_DEVICE_INFO = unsafe { mem::zeroed() };
+    info.cbSize = mem::size_of::<RID_DEVICE_INFO>() as u32;
+    let mut size = info.cbSize as UINT;
+    let result = unsafe {
+        GetRawInputDeviceInfoW(handle, RIDI_DEVICEINFO, &mut info as *mut _ as *mut _, &mut size)
+    };
+    if result == UINT::MAX || result == 0 {
+        return None;
+    }
+    let mouse = unsafe { info.u.mouse() };
+
+    let name = device_name(handle).unwrap_or_default();
+    let (vendor_id, product_id) = parse_vid_pid(&name);
+    let bus = if name.contains("HID#") { BusType::Usb } else if name.contains("ACPI#") { BusType::Ps2 } else { BusType::Other };
+
+    Some(PointingDevice {
+        name,
+        vendor_id,
+        product_id,
+        bus,
+        buttons: mouse.dwNumberOfButtons,
+        vertical_wheel,
+        horizontal_wheel: mouse.fHasHorizontalWheel != 0,
+        kind: DeviceKind::Mouse,
+    })
+}
+
+// Путь устройства вида "\\?\HID#VID_046D&PID_C077#..."
+fn device_name(handle: HANDLE) -> Option<String> {
+    let mut size: UINT = 0;
+    unsafe { GetRawInputDeviceInfoW(handle, RIDI_DEVICENAME, ptr::null_mut(), &mut size) };
+    if size == 0 {
+        return None;
+    }
+
+    let mut buffer = vec![0u16; size as usize];
+    let written = unsafe { GetRawInputDeviceInfoW(handle, RIDI_DEVICENAME, buffer.as_mut_ptr() as *mut _, &mut size) };
+    if written == UINT::MAX {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+fn parse_vid_pid(name: &str) -> (u16, u16) {
+    let upper = name.to_uppercase();
+    let find = |prefix: &str| {
+        upper
+            .find(prefix)
+            .and_then(|start| upper.get(start + prefix.len()..start + prefix.len() + 4))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .unwrap_or(0)
+    };
+    (find("VID_"), find("PID_"))
+}
@@ -0,0 +1,51 @@
+// Интеграционная проверка сервера 1: запрет адресов и ограничение частоты запросов
+mod common;
+
+use common::{connect, start_server_with_env};
+use protocol::{read_message, RejectReason, Request, Response, PROTOCOL_VERSION};
+use test_support::{request, TestDir};
+
+#[test]
+fn denied_address_gets_structured_rejection() {
+    // Запрет сильнее разрешения: адрес из обеих сетей отклоняется
+    let _server = start_server_with_env("3:1", &[("SERVER1_ALLOW", "127.0.0.0/8,::1"), ("SERVER1_DENY", "127.0.0.1")]);
+    let mut stream = connect();
+    let rejected = request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 6, server_kind: None, token: None });
+    assert!(
+        matches!(rejected, Response::Rejected { reason: RejectReason::AddressDenied, retry_after_ms: None, .. }),
+        "{:?}",
+        rejected
+    );
+    assert!(read_message::<_, Response>(&mut stream).unwrap().is_none());
+}
+
+#[test]
+fn client_exceeding_rate_limit_is_disconnected() {
+    let directory = TestDir::new("server1_rate_limit");
+    let config = directory.file("server1.toml", "[rate_limit]\nip_per_second = 1\nip_burst = 5\n");
+    let _server = start_server_with_env("3:1", &[("SERVER1_CONFIG", config.to_str().unwrap())]);
+    let mut stream = connect();
+    request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 7, server_kind: None, token: None });
+
+    // Подключение расходует один запрос из запаса, остальные - запросы клиента
+    let mut served = 0;
+    let rejected = loop {
+        match request(&mut stream, &Request::Get) {
+            Response::MouseInfo(_) => served += 1,
+            other => break other,
+        }
+        assert!(served < 10, "сервер не ограничил частоту запросов");
+    };
+    assert!(served >= 4, "отклонён {}-й запрос", served + 1);
+    assert!(
+        matches!(rejected, Response::Rejected { reason: RejectReason::RateLimited, retry_after_ms: Some(ms), .. } if ms > 0 && ms <= 1000),
+        "{:?}",
+        rejected
+    );
+    assert!(read_message::<_, Response>(&mut stream).unwrap().is_none());
+
+    // Запас адреса исчерпан: новое подключение отклоняется до приветствия
+    let mut again = connect();
+    let rejected = request(&mut again, &Request::Hello { version: PROTOCOL_VERSION, client_id: 8, server_kind: None, token: None });
+    assert!(matches!(rejected, Response::Rejected { reason: RejectReason::RateLimited, .. }), "{:?}", rejected);
+}
@@ -0,0 +1,52 @@
+// Запуск сервера 1 со сценарным источником сведений о мыши для интеграционных тестов.
+// Каждая программа тестов использует только часть функций
+#![allow(dead_code)]
+
+use std::net::TcpStream;
+use std::process::{Command, Output, Stdio};
+
+use protocol::{MouseInfo, Response};
+use test_support::ServerProcess;
+
+// Тестовый порт: запущенный на порту по умолчанию сервер 1 не мешает тестам
+pub const SERVER_ADDR: &str = "127.0.0.1:17870";
+
+pub fn start_server(script: &str) -> ServerProcess {
+    start_server_with_env(script, &[])
+}
+
+pub fn start_server_with_env(script: &str, vars: &[(&str, &str)]) -> ServerProcess {
+    spawn_server(script, vars, Stdio::inherit())
+}
+
+pub fn spawn_server(script: &str, vars: &[(&str, &str)], stderr: Stdio) -> ServerProcess {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server1"));
+    command
+        .env("SERVER1_LISTEN", SERVER_ADDR)
+        .env("SERVER1_MOUSE_PROVIDER", "fake")
+        .env("SERVER1_FAKE_MOUSE", script)
+        .envs(vars.iter().copied())
+        .stderr(stderr);
+    ServerProcess::spawn(command)
+}
+
+// Запуск сервера, который завершается сразу (вывод настроек или ошибка в них)
+pub fn run_server(args: &[&str], vars: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_server1"))
+        .args(args)
+        .envs(vars.iter().copied())
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Не удалось запустить сервер 1")
+}
+
+pub fn connect() -> TcpStream {
+    test_support::connect(SERVER_ADDR)
+}
+
+pub fn mouse(response: Response) -> (u32, bool) {
+    match response {
+        Response::MouseInfo(MouseInfo { mouse_buttons, has_scroll_wheel, .. }) => (mouse_buttons, has_scroll_wheel),
+        other => panic!("Ожидались сведения о мыши, получено: {:?}", other),
+    }
+}
@@ -0,0 +1,62 @@
+// Интеграционная проверка сервера 1: файл настроек, переменные окружения и ключи командной строки
+mod common;
+
+use common::run_server;
+use test_support::TestDir;
+
+#[test]
+fn print_config_merges_file_env_and_flags() {
+    let directory = TestDir::new("server1_merge");
+    let config = directory.file(
+        "server1.toml",
+        "[network]\nlisten = [\"127.0.0.1:7878\", \"[::1]:7878\"]\nmax_clients = 7\n\n[timeouts]\nread_ms = 1234\n",
+    );
+    let path = config.to_str().unwrap();
+
+    // Переменная окружения переопределяет файл, ключ командной строки - переменную окружения
+    let output = run_server(
+        &["--config", path, "--read-timeout-ms", "999", "--print-config"],
+        &[("SERVER1_MAX_CLIENTS", "8"), ("SERVER1_READ_TIMEOUT_MS", "500")],
+    );
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let printed = String::from_utf8(output.stdout).unwrap();
+    assert!(printed.contains("\"[::1]:7878\""), "{}", printed);
+    assert!(printed.contains("max_clients = 8"), "{}", printed);
+    assert!(printed.contains("read_ms = 999"), "{}", printed);
+    assert!(printed.contains("write_ms = 10000"), "{}", printed); // Значение по умолчанию
+}
+
+#[test]
+fn print_config_hides_client_tokens() {
+    let directory = TestDir::new("server1_tokens");
+    let config = directory.file("server1.toml", "[[auth.clients]]\nname = \"monitor\"\ntoken = \"secret-token-1\"\n");
+
+    let output = run_server(&["--config", config.to_str().unwrap(), "--print-config"], &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let printed = String::from_utf8(output.stdout).unwrap();
+    assert!(printed.contains("name = \"monitor\""), "{}", printed);
+    assert!(!printed.contains("secret-token-1"), "{}", printed);
+}
+
+#[test]
+fn invalid_config_names_offending_key() {
+    let cases = [
+        ("[subscription]\nmin_interval_ms = 0\n", "subscription.min_interval_ms"),
+        ("[network]\nmax_clients = \"many\"\n", "network.max_clients"),
+        ("[network]\nmax_clients = 4294967296\n", "network.max_clients"),
+        ("[timeouts]\nread_timeout = 5\n", "timeouts.read_timeout"),
+        ("[mouse]\nprovider = \"fake\"\n", "mouse.fake_script"),
+        ("[log]\nlevel = \"verbose==\"\n", "log.level"),
+        ("[log]\nformat = \"xml\"\n", "log.format"),
+        ("[access]\ndeny = [\"10.0.0.0/33\"]\n", "access.deny"),
+        ("[rate_limit]\nip_burst = 0\n", "rate_limit.ip_burst"),
+    ];
+    let directory = TestDir::new("server1_invalid");
+    for (index, (contents, key)) in cases.into_iter().enumerate() {
+        let config = directory.file(&format!("invalid{}.toml", index), contents);
+        let output = run_server(&["--config", config.to_str().unwrap()], &[]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(1), "{}", stderr);
+        assert!(stderr.contains(key), "ожидалось упоминание {}: {}", key, stderr);
+    }
+}
@@ -0,0 +1,42 @@
+// Интеграционная проверка сервера 1: предел клиентов и отключение молчащего клиента
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use common::{connect, mouse, start_server_with_env};
+use protocol::{read_message, write_message, Request, Response, PROTOCOL_VERSION};
+use test_support::request;
+
+#[test]
+fn client_over_limit_gets_busy_reply() {
+    let _server = start_server_with_env("3:1", &[("SERVER1_MAX_CLIENTS", "1")]);
+    let mut first = connect();
+    let hello = request(&mut first, &Request::Hello { version: PROTOCOL_VERSION, client_id: 3, server_kind: None, token: None });
+    assert!(matches!(hello, Response::Hello { .. }));
+
+    // Второй клиент сразу получает отказ, после чего сервер закрывает соединение
+    let mut second = connect();
+    let busy = request(&mut second, &Request::Hello { version: PROTOCOL_VERSION, client_id: 4, server_kind: None, token: None });
+    assert!(matches!(busy, Response::Busy { retry_after_ms } if retry_after_ms > 0));
+    assert!(read_message::<_, Response>(&mut second).unwrap().is_none());
+
+    // Первый клиент продолжает обслуживаться
+    assert_eq!(mouse(request(&mut first, &Request::Get)), (3, true));
+    write_message(&mut first, &Request::Disconnect).unwrap();
+}
+
+#[test]
+fn silent_client_is_disconnected() {
+    let _server = start_server_with_env("3:1", &[("SERVER1_READ_TIMEOUT_MS", "300")]);
+    let mut stream = connect();
+    request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 5, server_kind: None, token: None });
+
+    // Проверка связи продлевает соединение
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(request(&mut stream, &Request::Ping), Response::Pong);
+
+    // Без запросов сервер закрывает соединение по истечении таймаута чтения
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    assert!(read_message::<_, Response>(&mut stream).unwrap().is_none());
+}
@@ -0,0 +1,50 @@
+// Интеграционная проверка сервера 1: сбои журнала и занятый адрес не приводят к панике
+mod common;
+
+use std::net::TcpListener;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+
+use common::{connect, mouse, run_server, spawn_server};
+use protocol::{write_message, Request, PROTOCOL_VERSION};
+use test_support::request;
+
+// Сервер со сбоящим журналом продолжает обслуживать клиента, записи журнала попадают в stderr
+fn assert_log_falls_back_to_stderr(log_file: &str, expected: &str) {
+    let server = spawn_server("3:1", &[("SERVER1_LOG_FILE", log_file)], Stdio::piped());
+    let mut stream = connect();
+    request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 7, server_kind: None, token: None });
+    assert_eq!(mouse(request(&mut stream, &Request::Get)), (3, true));
+    write_message(&mut stream, &Request::Disconnect).unwrap();
+    thread::sleep(Duration::from_millis(300)); // Записи журнала выводятся отдельным потоком
+
+    let stderr = server.stop_with_stderr();
+    assert!(stderr.contains(expected), "{}", stderr);
+    assert!(stderr.contains("Клиент подключен") && stderr.contains("Клиент отключился"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+#[test]
+fn unopenable_log_file_falls_back_to_stderr() {
+    let log_file = std::env::temp_dir().join(format!("server1_missing_{}", std::process::id())).join("log.txt");
+    assert_log_falls_back_to_stderr(log_file.to_str().unwrap(), "не удалось открыть журнал");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn full_disk_log_falls_back_to_stderr() {
+    // Запись в /dev/full всегда завершается ошибкой «нет места на устройстве»
+    assert_log_falls_back_to_stderr("/dev/full", "Ошибка записи журнала /dev/full");
+}
+
+#[test]
+fn occupied_address_is_reported_without_panic() {
+    let occupied = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = occupied.local_addr().unwrap().to_string();
+    let output = run_server(&["--listen", &addr], &[("SERVER1_MOUSE_PROVIDER", "fake"), ("SERVER1_FAKE_MOUSE", "3:1")]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains(&format!("Не удалось открыть адрес {}", addr)), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
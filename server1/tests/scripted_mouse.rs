@@ -0,0 +1,49 @@
+// Интеграционная проверка сервера 1 со сценарным источником сведений о мыши
+mod common;
+
+use std::time::Duration;
+
+use common::{connect, mouse, start_server};
+use protocol::{read_message, write_message, Request, Response, ServerKind, PROTOCOL_VERSION};
+use test_support::request;
+
+#[test]
+fn server_follows_mouse_script() {
+    let _server = start_server("3:1,5:0");
+    let mut stream = connect();
+
+    let hello = request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 1, server_kind: None, token: None });
+    assert!(matches!(hello, Response::Hello { server_kind: ServerKind::MouseInfo, .. }));
+
+    assert_eq!(mouse(request(&mut stream, &Request::Get)), (3, true));
+    assert_eq!(mouse(request(&mut stream, &Request::Get)), (5, false));
+    assert_eq!(mouse(request(&mut stream, &Request::Get)), (5, false)); // Последний шаг повторяется
+
+    write_message(&mut stream, &Request::Disconnect).unwrap();
+}
+
+#[test]
+fn subscriber_receives_device_removed_event() {
+    let _server = start_server("3:1,0:0");
+    let mut stream = connect();
+    request(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 2, server_kind: None, token: None });
+
+    let subscribed = request(&mut stream, &Request::Subscribe { interval_ms: 100 });
+    assert_eq!(subscribed, Response::Subscribed { interval_ms: 100 });
+
+    // Второй шаг сценария убирает мышь; событие приходит после очередного опроса устройств
+    stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    loop {
+        match read_message(&mut stream).unwrap().expect("Сервер закрыл соединение") {
+            Response::DeviceRemoved { device, .. } => {
+                assert_eq!(device.buttons, 3);
+                assert!(device.vertical_wheel);
+                break;
+            }
+            Response::MouseInfo(_) => continue,
+            other => panic!("Неожиданный ответ: {:?}", other),
+        }
+    }
+
+    write_message(&mut stream, &Request::Disconnect).unwrap();
+}
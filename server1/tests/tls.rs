@@ -0,0 +1,189 @@
+// Интеграционная проверка TLS сервера 1 с сертификатами, созданными при запуске теста
+use std::io::{ErrorKind, Read};
+use std::net::TcpStream;
+use std::process::Command;
+use std::sync::Arc;
+
+use protocol::tls::{
+    client_config, fingerprint, parse_fingerprint, ClientIdentity, Connection, PrivateKeyDer, PrivatePkcs8KeyDer, ServerTrust,
+    TlsError,
+};
+use protocol::{read_message, write_message, AuthToken, Request, Response, ServerKind, PROTOCOL_VERSION};
+use rcgen::{BasicConstraints, CertificateParams, CertifiedKey, DnType, IsCa, KeyPair};
+use server_core::MAX_PENDING_REJECTS;
+use test_support::{request, ServerProcess, TestDir};
+
+// Порт, не пересекающийся с портом сервера в остальных тестах
+const SERVER_ADDR: &str = "127.0.0.1:17877";
+
+// Запущенный сервер и каталог с его сертификатом; каталог удаляется после остановки сервера
+struct TlsServer {
+    _server: ServerProcess,
+    _directory: TestDir,
+}
+
+// Сертификат сервера для localhost и 127.0.0.1: подписанный CA или самоподписанный
+fn server_certificate(issuer: Option<&CertifiedKey>) -> CertifiedKey {
+    let key_pair = KeyPair::generate().unwrap();
+    let params = CertificateParams::new(vec!["localhost".to_string(), "127.0.0.1".to_string()]).unwrap();
+    let cert = match issuer {
+        Some(ca) => params.signed_by(&key_pair, &ca.cert, &ca.key_pair).unwrap(),
+        None => params.self_signed(&key_pair).unwrap(),
+    };
+    CertifiedKey { cert, key_pair }
+}
+
+fn certificate_authority(name: &str) -> CertifiedKey {
+    let key_pair = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.distinguished_name.push(DnType::CommonName, name);
+    let cert = params.self_signed(&key_pair).unwrap();
+    CertifiedKey { cert, key_pair }
+}
+
+// auth - таблицы [[auth.clients]] файла настроек сервера
+fn start_server(name: &str, certificate: &CertifiedKey, auth: &str) -> TlsServer {
+    let directory = TestDir::new(&format!("server1_tls_{}", name));
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server1"));
+    command
+        .env("SERVER1_CONFIG", directory.file("server1.toml", auth))
+        .env("SERVER1_LISTEN", SERVER_ADDR)
+        .env("SERVER1_TLS_CERT", directory.file("server.pem", certificate.cert.pem()))
+        .env("SERVER1_TLS_KEY", directory.file("server.key", certificate.key_pair.serialize_pem()))
+        .env("SERVER1_MOUSE_PROVIDER", "fake")
+        .env("SERVER1_FAKE_MOUSE", "3:1")
+        .env("SERVER1_LOG_FILE", directory.path().join("server_log.txt"));
+    TlsServer { _server: ServerProcess::spawn(command), _directory: directory }
+}
+
+fn connect_tcp() -> TcpStream {
+    test_support::connect(SERVER_ADDR)
+}
+
+fn connect(server_name: &str, trust: ServerTrust) -> Result<Connection, TlsError> {
+    connect_as(server_name, trust, None)
+}
+
+fn connect_as(server_name: &str, trust: ServerTrust, identity: Option<&CertifiedKey>) -> Result<Connection, TlsError> {
+    let identity = identity.map(|certificate| ClientIdentity {
+        certificates: vec![certificate.cert.der().clone()],
+        key: PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certificate.key_pair.serialize_der())),
+    });
+    Connection::tls(connect_tcp(), server_name, Arc::new(client_config(&trust, identity).unwrap()))
+}
+
+fn hello(connection: &mut Connection, token: Option<&str>) -> Response {
+    let token = token.map(|token| AuthToken(token.to_string()));
+    let hello = Request::Hello { version: PROTOCOL_VERSION, client_id: 1, server_kind: Some(ServerKind::MouseInfo), token };
+    request(connection, &hello)
+}
+
+// Приветствие и запрос данных по установленному соединению
+fn assert_mouse_served(connection: &mut Connection) {
+    let response = hello(connection, None);
+    assert!(matches!(response, Response::Hello { server_kind: ServerKind::MouseInfo, .. }), "{:?}", response);
+    request_mouse(connection);
+}
+
+fn request_mouse(connection: &mut Connection) {
+    let response = request(connection, &Request::Get);
+    assert!(matches!(response, Response::MouseInfo(_)), "{:?}", response);
+    write_message(connection, &Request::Disconnect).unwrap();
+    let _ = connection.shutdown(); // Сервер мог уже закрыть соединение после Disconnect
+}
+
+#[test]
+fn certificate_signed_by_ca_is_verified() {
+    let ca = certificate_authority("Тестовый CA");
+    let _server = start_server("ca", &server_certificate(Some(&ca)), "");
+
+    let mut connection = connect("localhost", ServerTrust::Ca(vec![ca.cert.der().clone()])).unwrap();
+    assert_mouse_served(&mut connection);
+    let mut connection = connect("127.0.0.1", ServerTrust::Ca(vec![ca.cert.der().clone()])).unwrap();
+    assert_mouse_served(&mut connection);
+
+    // Имя не из сертификата и сертификат другого CA отклоняются
+    let error = connect("example.com", ServerTrust::Ca(vec![ca.cert.der().clone()])).err().unwrap();
+    assert!(matches!(error, TlsError::Handshake(_)), "{}", error);
+    let other = certificate_authority("Другой CA");
+    let error = connect("localhost", ServerTrust::Ca(vec![other.cert.der().clone()])).err().unwrap();
+    assert!(matches!(error, TlsError::Handshake(_)), "{}", error);
+}
+
+#[test]
+fn self_signed_certificate_is_pinned_by_fingerprint() {
+    let certificate = server_certificate(None);
+    let _server = start_server("pinned", &certificate, "");
+
+    let pinned = parse_fingerprint(&fingerprint(certificate.cert.der())).unwrap();
+    let mut connection = connect("localhost", ServerTrust::Pinned(vec![pinned])).unwrap();
+    assert_mouse_served(&mut connection);
+
+    let other = parse_fingerprint(&fingerprint(server_certificate(None).cert.der())).unwrap();
+    let error = connect("localhost", ServerTrust::Pinned(vec![other])).err().unwrap();
+    assert!(error.to_string().contains("не совпадает"), "{}", error);
+}
+
+#[test]
+fn plaintext_client_is_not_served() {
+    let certificate = server_certificate(None);
+    let _server = start_server("plaintext", &certificate, "");
+
+    // Кадр приветствия без TLS сервер не принимает за рукопожатие и закрывает соединение
+    let mut stream = connect_tcp();
+    write_message(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id: 2, server_kind: None, token: None }).unwrap();
+    let response = read_message::<_, Response>(&mut stream);
+    assert!(!matches!(response, Ok(Some(Response::Hello { .. }))), "{:?}", response);
+}
+
+#[test]
+fn clients_are_identified_by_token_or_certificate() {
+    let certificate = server_certificate(None);
+    let trust = || ServerTrust::Pinned(vec![parse_fingerprint(&fingerprint(certificate.cert.der())).unwrap()]);
+    let console = server_certificate(None);
+    let auth = format!(
+        "[[auth.clients]]\nname = \"console\"\ncertificate = \"{}\"\n\n[[auth.clients]]\nname = \"operator\"\ntoken = \"secret\"\n",
+        fingerprint(console.cert.der())
+    );
+    let _server = start_server("auth", &certificate, &auth);
+
+    let mut connection = connect_as("localhost", trust(), Some(&console)).unwrap();
+    let response = hello(&mut connection, None);
+    assert!(matches!(&response, Response::Hello { identity: Some(name), .. } if name == "console"), "{:?}", response);
+    request_mouse(&mut connection);
+
+    let mut connection = connect("localhost", trust()).unwrap();
+    let response = hello(&mut connection, Some("secret"));
+    assert!(matches!(&response, Response::Hello { identity: Some(name), .. } if name == "operator"), "{:?}", response);
+    request_mouse(&mut connection);
+
+    // Без токена, с неверным токеном, с чужим сертификатом и с токеном другого клиента доступ закрыт
+    let stranger = server_certificate(None);
+    let cases = [(None, None, "требует"), (None, Some("wrong"), "неверный"), (Some(&stranger), None, "не зарегистрирован"),
+                 (Some(&console), Some("secret"), "разным клиентам")];
+    for (identity, token, reason) in cases {
+        let mut connection = connect_as("localhost", trust(), identity).unwrap();
+        match hello(&mut connection, token) {
+            Response::Unauthorized { message } => assert!(message.contains(reason), "{}", message),
+            other => panic!("Ожидался отказ в доступе, получено: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn rejects_over_limit_are_closed_without_reply() {
+    let _server = start_server("rejects", &server_certificate(None), "[network]\nmax_clients = 1\n");
+
+    // Клиенты не начинают рукопожатие TLS: первый занимает место клиента, следующие - места отказов,
+    // пока не истечёт таймаут чтения (30 с)
+    let _held: Vec<TcpStream> = (0..=MAX_PENDING_REJECTS).map(|_| connect_tcp()).collect();
+
+    // Сверх предела отказов соединение закрывается сразу, без рукопожатия и ответа
+    let mut excess = connect_tcp();
+    match excess.read(&mut [0u8; 1]) {
+        Ok(0) => {}
+        Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+        other => panic!("Ожидалось закрытие соединения, получено: {:?}", other),
+    }
+}
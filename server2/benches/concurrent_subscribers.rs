@@ -0,0 +1,173 @@
+// Нагрузочный тест: тысячи одновременных подписчиков сервера 2.
+// Запуск: cargo bench -p server2 --bench concurrent_subscribers
+// Параметры: BENCH_CLIENTS (по умолчанию 2000), BENCH_SECONDS (10), BENCH_INTERVAL_MS (1000)
+
+use std::env;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use protocol::{read_message_async, write_message_async, Request, Response, PROTOCOL_VERSION};
+use test_support::ServerProcess;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout, Instant};
+
+// Порт теста: запущенный на порту по умолчанию сервер 2 не мешает измерению
+const SERVER_ADDR: &str = "127.0.0.1:17882";
+const CONNECT_ATTEMPTS: u32 = 50;
+
+fn env_or(name: &str, default: u64) -> u64 {
+    env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+// Подключение с повторами: сервер может ещё запускаться, а очередь приёма - быть заполненной
+async fn connect() -> std::io::Result<TcpStream> {
+    let mut attempt = 0;
+    loop {
+        match TcpStream::connect(SERVER_ADDR).await {
+            Ok(stream) => return Ok(stream),
+            Err(_) if attempt < CONNECT_ATTEMPTS => {
+                attempt += 1;
+                sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Ожидание готовности запущенного сервера. Сервер отвечает своим PID: на порту мог оказаться
+// другой процесс, а запущенный сервер - завершиться, не заняв порт
+async fn wait_until_ready(server: &mut ServerProcess) -> Result<(), String> {
+    for _ in 0..CONNECT_ATTEMPTS {
+        if let Some(status) = server.exit_status() {
+            return Err(format!("сервер 2 завершился при запуске: {}", status));
+        }
+        if let Ok(mut stream) = TcpStream::connect(SERVER_ADDR).await {
+            let hello = Request::Hello { version: PROTOCOL_VERSION, client_id: u64::MAX, server_kind: None, token: None };
+            write_message_async(&mut stream, &hello).await.map_err(|e| e.to_string())?;
+            read_message_async::<_, Response>(&mut stream).await.map_err(|e| e.to_string())?;
+            write_message_async(&mut stream, &Request::Get).await.map_err(|e| e.to_string())?;
+            return match read_message_async::<_, Response>(&mut stream).await {
+                Ok(Some(Response::ProcessInfo(info))) if info.pid == server.id() => {
+                    let _ = write_message_async(&mut stream, &Request::Disconnect).await;
+                    Ok(())
+                }
+                other => Err(format!("на {} отвечает другой сервер: {:?}", SERVER_ADDR, other)),
+            };
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    Err(format!("сервер 2 не принимает подключения на {}", SERVER_ADDR))
+}
+
+// Итог одного подписчика
+struct SubscriberStats {
+    connected: Duration, // Время подключения, приветствия и подписки
+    pushes: u64,         // Получено рассылок
+    expected: u64,       // Ожидалось рассылок за время подписки
+}
+
+// Один подписчик: подключение и подсчёт рассылок до момента deadline
+async fn subscriber(client_id: u64, interval_ms: u64, deadline: Instant) -> Result<SubscriberStats, String> {
+    let started = Instant::now();
+    let mut stream = connect().await.map_err(|e| format!("подключение: {}", e))?;
+
+    write_message_async(&mut stream, &Request::Hello { version: PROTOCOL_VERSION, client_id, server_kind: None, token: None })
+        .await
+        .map_err(|e| e.to_string())?;
+    match read_message_async::<_, Response>(&mut stream).await {
+        Ok(Some(Response::Hello { .. })) => {}
+        other => return Err(format!("приветствие: {:?}", other)),
+    }
+    write_message_async(&mut stream, &Request::Subscribe { interval_ms })
+        .await
+        .map_err(|e| e.to_string())?;
+    let connected = started.elapsed();
+
+    let mut pushes = 0;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match timeout(remaining, read_message_async::<_, Response>(&mut stream)).await {
+            Err(_) => break, // Время теста истекло
+            Ok(Ok(Some(Response::ProcessInfo(_)))) => pushes += 1,
+            Ok(Ok(Some(_))) => {}
+            Ok(Ok(None)) => return Err("соединение закрыто сервером".to_string()),
+            Ok(Err(e)) => return Err(e.to_string()),
+        }
+    }
+    let _ = write_message_async(&mut stream, &Request::Disconnect).await;
+
+    // Первая рассылка приходит сразу после подписки
+    let subscribed_ms = deadline.saturating_duration_since(started + connected).as_millis() as u64;
+    Ok(SubscriberStats { connected, pushes, expected: subscribed_ms / interval_ms + 1 })
+}
+
+#[tokio::main]
+async fn main() {
+    let clients = env_or("BENCH_CLIENTS", 2000);
+    let seconds = env_or("BENCH_SECONDS", 10);
+    let interval_ms = env_or("BENCH_INTERVAL_MS", 1000);
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server2"));
+    command
+        .env("SERVER2_LISTEN", SERVER_ADDR)
+        .env("SERVER2_MAX_CLIENTS", (clients + 1).to_string()) // Ещё одно место - для проверки готовности
+        .env("SERVER2_READ_TIMEOUT_MS", ((seconds + 60) * 1000).to_string()) // Подписчики не отправляют Ping
+        .stdout(Stdio::null());
+    let mut server = ServerProcess::spawn(command);
+    if let Err(e) = wait_until_ready(&mut server).await {
+        eprintln!("Ошибка запуска: {}", e);
+        drop(server); // exit не выполняет деструкторы
+        std::process::exit(1);
+    }
+
+    println!("Подписчиков: {}, длительность: {} с, интервал рассылки: {} мс", clients, seconds, interval_ms);
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(seconds);
+    let tasks: Vec<_> = (0..clients)
+        .map(|client_id| tokio::spawn(subscriber(client_id, interval_ms, deadline)))
+        .collect();
+
+    let mut connect_times = Vec::new();
+    let mut pushes = 0;
+    let mut expected = 0;
+    let mut failures = 0;
+    for task in tasks {
+        match task.await.expect("Задача подписчика завершилась аварийно") {
+            Ok(stats) => {
+                connect_times.push(stats.connected);
+                pushes += stats.pushes;
+                expected += stats.expected;
+            }
+            Err(e) => {
+                failures += 1;
+                if failures <= 5 {
+                    println!("Ошибка подписчика: {}", e);
+                }
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    connect_times.sort();
+    let percentile = |p: usize| connect_times.get(connect_times.len().saturating_sub(1) * p / 100).copied().unwrap_or_default();
+
+    println!("Подключено: {}, ошибок: {}", connect_times.len(), failures);
+    println!(
+        "Подключение и подписка: медиана {:?}, 99% {:?}, максимум {:?}",
+        percentile(50),
+        percentile(99),
+        percentile(100)
+    );
+    println!(
+        "Получено рассылок: {} из ожидаемых ~{} ({:.0} в секунду за {:.1} с)",
+        pushes,
+        expected,
+        pushes as f64 / elapsed.as_secs_f64(),
+        elapsed.as_secs_f64()
+    );
+
+    if failures > 0 {
+        drop(server);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::io;
+
+use chrono::Local;
+use protocol::HostInfo;
+
+// Имя и версия системы из uname(2)
+struct Uname {
+    sysname: String,
+    nodename: String,
+    release: String,
+    machine: String,
+}
+
+#[cfg(unix)]
+fn uname() -> io::Result<Uname> {
+    use std::ffi::CStr;
+
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Поля utsname - строки с завершающим нулём
+    let field = |value: &[libc::c_char]| unsafe { CStr::from_ptr(value.as_ptr()) }.to_string_lossy().into_owned();
+
+    Ok(Uname {
+        sysname: field(&name.sysname),
+        nodename: field(&name.nodename),
+        release: field(&name.release),
+        machine: field(&name.machine),
+    })
+}
+
+#[cfg(not(unix))]
+fn uname() -> io::Result<Uname> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "uname недоступен на этой ОС"))
+}
+
+#[cfg(unix)]
+fn cpu_count() -> u32 {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    if count > 0 { count as u32 } else { 1 }
+}
+
+#[cfg(not(unix))]
+fn cpu_count() -> u32 {
+    std::thread::available_parallelism().map_or(1, |count| count.get() as u32)
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// Время работы системы из /proc/uptime: "<секунды с загрузки> <секунды простоя>"
+fn parse_uptime_ms(uptime: &str) -> io::Result<u64> {
+    uptime
+        .split_whitespace()
+        .next()
+        .and_then(|seconds| seconds.parse::<f64>().ok())
+        .map(|seconds| (seconds * 1000.0) as u64)
+        .ok_or_else(|| invalid_data(format!("некорректный формат /proc/uptime: {}", uptime.trim())))
+}
+
+// Средняя загрузка из /proc/loadavg: первые три поля
+fn parse_load_average(loadavg: &str) -> io::Result<[f64; 3]> {
+    let values: Vec<f64> = loadavg
+        .split_whitespace()
+        .take(3)
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    values
+        .try_into()
+        .map_err(|_| invalid_data(format!("некорректный формат /proc/loadavg: {}", loadavg.trim())))
+}
+
+// Объём памяти в байтах из строки /proc/meminfo вида "MemTotal:  16318480 kB"
+fn meminfo_bytes(meminfo: &str, key: &str) -> io::Result<u64> {
+    meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+        .map(|kilobytes| kilobytes * 1024)
+        .ok_or_else(|| invalid_data(format!("в /proc/meminfo нет строки {}", key)))
+}
+
+// Сведения о системе из /proc и uname
+pub fn read_host_info() -> io::Result<HostInfo> {
+    let name = uname()?;
+    let meminfo = fs::read_to_string("/proc/meminfo")?;
+
+    Ok(HostInfo {
+        hostname: name.nodename,
+        os: name.sysname,
+        kernel_release: name.release,
+        architecture: name.machine,
+        uptime_ms: parse_uptime_ms(&fs::read_to_string("/proc/uptime")?)?,
+        load_average: parse_load_average(&fs::read_to_string("/proc/loadavg")?)?,
+        total_memory_bytes: meminfo_bytes(&meminfo, "MemTotal")?,
+        available_memory_bytes: meminfo_bytes(&meminfo, "MemAvailable")?,
+        cpu_count: cpu_count(),
+        timestamp: Local::now().timestamp(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uptime_is_read_in_milliseconds() {
+        assert_eq!(parse_uptime_ms("350735.47 234388.90\n").unwrap(), 350_735_470);
+        assert_eq!(parse_uptime_ms("12 3").unwrap(), 12_000);
+        for invalid in ["", "\n", "abc 1.0"] {
+            assert_eq!(parse_uptime_ms(invalid).unwrap_err().kind(), io::ErrorKind::InvalidData, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn load_average_takes_first_three_fields() {
+        assert_eq!(parse_load_average("0.52 0.58 0.59 2/1064 123456\n").unwrap(), [0.52, 0.58, 0.59]);
+        assert!(parse_load_average("0.52 0.58").is_err());
+        assert!(parse_load_average("0.52 x 0.59 2/1064").is_err());
+    }
+
+    #[test]
+    fn meminfo_values_are_converted_to_bytes() {
+        let meminfo = "MemTotal:       16318480 kB\nMemFree:         1020304 kB\nMemAvailable:    8388608 kB\nHugePages_Total:       0\n";
+        assert_eq!(meminfo_bytes(meminfo, "MemTotal").unwrap(), 16_318_480 * 1024);
+        assert_eq!(meminfo_bytes(meminfo, "MemAvailable").unwrap(), 8 * 1024 * 1024 * 1024);
+        // Совпадение только целого ключа: "Mem" не находит "MemTotal"
+        assert!(meminfo_bytes(meminfo, "Mem").is_err());
+        assert!(meminfo_bytes(meminfo, "SwapTotal").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+// Сервер 2: источник сведений о процессах и системе; используется программой server2 и общим сервером
+mod host;
+mod procfs;
+
+use std::io::ErrorKind;
+use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
+use std::hash::BuildHasher;
+use std::collections::hash_map::RandomState;
+use std::sync::{Arc, Mutex, PoisonError};
+use chrono::Local;
+use protocol::{message_size, FrameError, LookupFailure, ProcessDetails, ProcessInfo, ProcessMetrics, Request, Response, ServerKind, MAX_FRAME_SIZE};
+use server_core::{DataProvider, ServerSpec};
+use tracing::{info, warn};
+
+pub static SPEC: ServerSpec = ServerSpec {
+    name: "server2",
+    title: "Сервер 2",
+    about: "Сервер 2: сведения о процессе сервера и о системе",
+    version: env!("CARGO_PKG_VERSION"),
+    env_prefix: "SERVER2",
+    default_port: 7879,
+    default_log_file: "server2_log.txt",
+};
+
+// Структура для хранения состояния сервера
+struct ServerState {
+    start_time: Instant,               // Момент запуска сервера (монотонные часы, не зависят от перевода системного времени)
+    instance_id: String,               // Идентификатор запуска, по которому клиент замечает перезапуск
+    clock: Option<procfs::SystemClock>, // Параметры для пересчёта тиков из /proc (None - /proc недоступен)
+    metrics: Option<(Instant, Option<ProcessMetrics>)>, // Последние прочитанные метрики и момент чтения
+}
+
+impl ServerState {
+    fn new() -> Self {
+        let start_time = Instant::now();
+        let clock = procfs::SystemClock::read().ok();
+        ServerState { start_time, instance_id: new_instance_id(), clock, metrics: None }
+    }
+}
+
+// Случайный идентификатор запуска: хеш PID и времени старта со случайным ключом
+fn new_instance_id() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{:016x}", RandomState::new().hash_one((std::process::id(), now)))
+}
+
+// Время, в течение которого метрики процесса отдаются всем подписчикам без повторного чтения /proc
+// (чтение /proc/self/fd занимает время, пропорциональное числу подключений)
+const METRICS_CACHE_TTL: Duration = Duration::from_millis(100);
+
+// Получение информации о процессе сервера
+fn process_info(state: &Mutex<ServerState>) -> Response {
+    let pid = std::process::id(); // Идентификатор процесса
+    let (uptime, instance_id, metrics) = {
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        let metrics = match &state.metrics {
+            Some((read_at, metrics)) if read_at.elapsed() < METRICS_CACHE_TTL => metrics.clone(),
+            _ => {
+                let metrics = state.clock.and_then(|clock| procfs::read_metrics("/proc/self", &clock).ok()); // Метрики процесса
+                state.metrics = Some((Instant::now(), metrics.clone()));
+                metrics
+            }
+        };
+        (state.start_time.elapsed(), state.instance_id.clone(), metrics)
+    };
+
+    Response::ProcessInfo(ProcessInfo {
+        pid,
+        instance_id,
+        uptime_ms: uptime.as_millis() as u64, // Время работы сервера
+        metrics,
+        timestamp: Local::now().timestamp(),
+    })
+}
+
+// Поиск процессов с преобразованием ошибок ввода-вывода в структурированный ответ
+fn lookup_process<F>(state: &Mutex<ServerState>, lookup: F, query: &str) -> Response
+where
+    F: FnOnce(&procfs::SystemClock) -> std::io::Result<Vec<ProcessDetails>>,
+{
+    let Some(clock) = state.lock().unwrap_or_else(PoisonError::into_inner).clock else {
+        return Response::ProcessLookupFailed {
+            reason: LookupFailure::Unsupported,
+            message: "сведения о процессах недоступны: /proc не найден".to_string(),
+        };
+    };
+
+    match lookup(&clock) {
+        Ok(processes) if processes.is_empty() => Response::ProcessLookupFailed {
+            reason: LookupFailure::NotFound,
+            message: format!("процесс \"{}\" не найден", query),
+        },
+        Ok(processes) => fit_processes(processes, query),
+        Err(e) => {
+            let reason = match e.kind() {
+                ErrorKind::NotFound => LookupFailure::NotFound,
+                ErrorKind::PermissionDenied => LookupFailure::PermissionDenied,
+                _ => LookupFailure::Other,
+            };
+            Response::ProcessLookupFailed { reason, message: format!("процесс \"{}\": {}", query, e) }
+        }
+    }
+}
+
+// Число первых процессов списка, сведения о которых помещаются в один кадр ответа
+fn fitting_count(processes: &[ProcessDetails]) -> Result<usize, FrameError> {
+    let mut size = message_size(&Response::Processes { processes: Vec::new() })?;
+    for (index, process) in processes.iter().enumerate() {
+        // Элементы массива JSON разделены запятыми
+        size += message_size(process)? + usize::from(index > 0);
+        if size > MAX_FRAME_SIZE {
+            return Ok(index);
+        }
+    }
+    Ok(processes.len())
+}
+
+// Ответ со списком процессов, укороченным до помещающегося в один кадр
+fn fit_processes(mut processes: Vec<ProcessDetails>, query: &str) -> Response {
+    let fitting = match fitting_count(&processes) {
+        Ok(fitting) => fitting,
+        Err(e) => return Response::Error { message: format!("процесс \"{}\": {}", query, e) },
+    };
+    if fitting == 0 {
+        return Response::ProcessLookupFailed {
+            reason: LookupFailure::Other,
+            message: format!("процесс \"{}\": сведения не помещаются в ответ", query),
+        };
+    }
+    if fitting < processes.len() {
+        warn!(found = processes.len(), sent = fitting, "Найденные процессы не помещаются в ответ, список сокращён");
+        processes.truncate(fitting);
+    }
+    Response::Processes { processes }
+}
+
+// Данные сервера 2: сведения о собственном процессе, поиск процессов и сведения о системе
+struct ProcessData {
+    state: Mutex<ServerState>,
+}
+
+impl DataProvider for ProcessData {
+    fn kind(&self) -> ServerKind {
+        ServerKind::ProcessInfo
+    }
+
+    fn capabilities(&self) -> Vec<String> {
+        vec!["process_lookup".to_string(), "host_info".to_string()]
+    }
+
+    fn snapshot(&self) -> Response {
+        process_info(&self.state)
+    }
+
+    fn handle(&self, request: &Request) -> Option<Response> {
+        let response = match request {
+            Request::ProcessInfo { pid } => {
+                let pid = *pid;
+                info!(pid, "Запрос сведений о процессе");
+                lookup_process(
+                    &self.state,
+                    |clock| procfs::read_details(pid, clock, &procfs::read_users()).map(|details| vec![details]),
+                    &pid.to_string(),
+                )
+            }
+            Request::ProcessFind { name } => {
+                info!(name = %name, "Поиск процессов");
+                lookup_process(&self.state, |clock| procfs::find_by_name(name, clock), name)
+            }
+            Request::HostInfo => match host::read_host_info() {
+                Ok(info) => Response::HostInfo(info),
+                Err(e) => Response::Error { message: format!("Не удалось получить сведения о системе: {}", e) },
+            },
+            _ => return None,
+        };
+        Some(response)
+    }
+
+    fn start(&self) {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        info!(instance_id = %state.instance_id, "Идентификатор запуска");
+    }
+}
+
+// Источник сведений о процессе сервера, поиске процессов и системе
+pub fn process_data() -> Arc<dyn DataProvider> {
+    Arc::new(ProcessData { state: Mutex::new(ServerState::new()) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(pid: u32, command_line: String) -> ProcessDetails {
+        ProcessDetails {
+            pid,
+            command_line,
+            state: "S (sleeping)".to_string(),
+            uptime_ms: 1000,
+            rss_bytes: 4096,
+            virtual_memory_bytes: 8192,
+            owner: "root".to_string(),
+        }
+    }
+
+    #[test]
+    fn found_processes_are_trimmed_to_one_frame() {
+        let processes: Vec<_> = (0..100).map(|pid| details(pid, "x".repeat(1024))).collect();
+        let response = fit_processes(processes, "x");
+        let Response::Processes { processes } = &response else { panic!("{:?}", response) };
+        assert!(processes.len() < 100 && processes.len() > 50, "{}", processes.len());
+        assert!(message_size(&response).unwrap() <= MAX_FRAME_SIZE);
+        // Ещё один процесс в ответ уже не помещается
+        let mut longer = processes.clone();
+        longer.push(details(100, "x".repeat(1024)));
+        assert!(message_size(&Response::Processes { processes: longer }).unwrap() > MAX_FRAME_SIZE);
+    }
+
+    #[test]
+    fn fitting_list_is_unchanged() {
+        let processes = vec![details(1, "init".to_string()), details(2, "[kthreadd]".to_string())];
+        assert_eq!(fit_processes(processes.clone(), "x"), Response::Processes { processes });
+    }
+
+    #[test]
+    fn process_larger_than_frame_is_a_lookup_failure() {
+        let response = fit_processes(vec![details(1, "x".repeat(MAX_FRAME_SIZE))], "x");
+        assert!(matches!(response, Response::ProcessLookupFailed { reason: LookupFailure::Other, .. }), "{:?}", response);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use protocol::{ProcessDetails, ProcessMetrics};
+
+// Максимальное число процессов в ответе на поиск по имени
+const MAX_FIND_RESULTS: usize = 100;
+// Предельная длина командной строки в ответе (байт): аргументы бывают длиной в мегабайты
+const MAX_COMMAND_LINE_LEN: usize = 1024;
+
+// Сведения о системе, не меняющиеся за время работы сервера
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    pub clock_ticks: u64, // Тиков процессорного времени в секунду (USER_HZ)
+    pub boot_time: i64,   // Время загрузки системы (секунды UNIX)
+}
+
+impl SystemClock {
+    pub fn read() -> io::Result<Self> {
+        let stat = fs::read_to_string("/proc/stat")?;
+        let boot_time = stat
+            .lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|value| value.trim().parse().ok())
+            .ok_or_else(|| invalid_data("в /proc/stat нет строки btime"))?;
+
+        Ok(SystemClock { clock_ticks: clock_ticks(), boot_time })
+    }
+}
+
+#[cfg(unix)]
+fn clock_ticks() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 { ticks as u64 } else { 100 }
+}
+
+#[cfg(not(unix))]
+fn clock_ticks() -> u64 {
+    100
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Поля /proc/<pid>/stat после имени процесса в скобках
+struct Stat {
+    fields: Vec<String>,
+}
+
+impl Stat {
+    fn read(proc_dir: &str) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(format!("{}/stat", proc_dir))?)
+    }
+
+    // Имя процесса может содержать пробелы и скобки, поэтому поля отсчитываются от последней ')'
+    fn parse(stat: &str) -> io::Result<Self> {
+        let after_comm = stat
+            .rfind(')')
+            .map(|end| &stat[end + 1..])
+            .ok_or_else(|| invalid_data("некорректный формат stat"))?;
+        Ok(Stat { fields: after_comm.split_whitespace().map(str::to_string).collect() })
+    }
+
+    // Поле по номеру из proc(5); поле 3 (state) идёт первым после имени
+    fn field(&self, number: usize) -> io::Result<u64> {
+        self.fields
+            .get(number - 3)
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| invalid_data(&format!("в stat нет поля {}", number)))
+    }
+
+    // Время запуска процесса в миллисекундах UNIX
+    fn start_time_ms(&self, clock: &SystemClock) -> io::Result<i64> {
+        Ok(clock.boot_time * 1000 + (self.field(22)? * 1000 / clock.clock_ticks) as i64)
+    }
+}
+
+// Значение строки /proc/<pid>/status вида "Key:\tvalue"
+fn status_value<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .and_then(|rest| rest.strip_prefix(':'))
+        .map(str::trim)
+}
+
+// Размер в байтах из строки status в килобайтах; у потоков ядра строк VmRSS/VmSize нет
+fn status_bytes(status: &str, key: &str) -> u64 {
+    status_value(status, key)
+        .and_then(|value| value.trim_end_matches("kB").trim().parse::<u64>().ok())
+        .unwrap_or(0)
+        * 1024
+}
+
+// Метрики процесса из /proc/<pid>/stat, /proc/<pid>/status и /proc/<pid>/fd
+pub fn read_metrics(proc_dir: &str, clock: &SystemClock) -> io::Result<ProcessMetrics> {
+    let stat = Stat::read(proc_dir)?;
+    let status = fs::read_to_string(format!("{}/status", proc_dir))?;
+    let open_fds = fs::read_dir(format!("{}/fd", proc_dir))?.count() as u32;
+    let ticks_to_ms = |ticks: u64| ticks * 1000 / clock.clock_ticks;
+
+    Ok(ProcessMetrics {
+        rss_bytes: status_bytes(&status, "VmRSS"),
+        virtual_memory_bytes: status_bytes(&status, "VmSize"),
+        user_cpu_ms: ticks_to_ms(stat.field(14)?),
+        system_cpu_ms: ticks_to_ms(stat.field(15)?),
+        threads: stat.field(20)? as u32,
+        open_fds,
+        start_time: stat.start_time_ms(clock)? / 1000,
+    })
+}
+
+// Сведения о произвольном процессе; users - имена пользователей по UID из read_users
+pub fn read_details(pid: u32, clock: &SystemClock, users: &Users) -> io::Result<ProcessDetails> {
+    let proc_dir = format!("/proc/{}", pid);
+    let stat = Stat::read(&proc_dir)?;
+    let status = fs::read_to_string(format!("{}/status", proc_dir))?;
+
+    // Аргументы командной строки разделены нулевыми байтами
+    let cmdline = fs::read(format!("{}/cmdline", proc_dir))?;
+    let command_line = cmdline
+        .split(|&byte| byte == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let command_line = if command_line.is_empty() {
+        format!("[{}]", status_value(&status, "Name").unwrap_or("?")) // Поток ядра
+    } else {
+        truncate(command_line, MAX_COMMAND_LINE_LEN)
+    };
+
+    let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or(0);
+    let uid = status_value(&status, "Uid")
+        .and_then(|value| value.split_whitespace().next())
+        .unwrap_or("?");
+
+    Ok(ProcessDetails {
+        pid,
+        command_line,
+        state: status_value(&status, "State").unwrap_or("?").to_string(),
+        uptime_ms: (now_ms - stat.start_time_ms(clock)?).max(0) as u64,
+        rss_bytes: status_bytes(&status, "VmRSS"),
+        virtual_memory_bytes: status_bytes(&status, "VmSize"),
+        owner: users.get(uid).cloned().unwrap_or_else(|| uid.to_string()),
+    })
+}
+
+// Обрезка строки до max_len байт по границе символа с многоточием в конце
+fn truncate(mut text: String, max_len: usize) -> String {
+    if text.len() > max_len {
+        let mut end = max_len - '…'.len_utf8();
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+// Поиск процессов, у которых имя (comm) или имя исполняемого файла совпадает с name
+pub fn find_by_name(name: &str, clock: &SystemClock) -> io::Result<Vec<ProcessDetails>> {
+    let users = read_users();
+    let mut found = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let Some(pid) = entry?.file_name().to_str().and_then(|pid| pid.parse::<u32>().ok()) else {
+            continue;
+        };
+
+        // Процесс мог завершиться во время обхода - такие пропускаются
+        let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) else { continue };
+        let executable = fs::read(format!("/proc/{}/cmdline", pid))
+            .ok()
+            .and_then(|cmdline| cmdline.split(|&byte| byte == 0).next().map(|arg| String::from_utf8_lossy(arg).into_owned()))
+            .unwrap_or_default();
+        let executable = executable.rsplit('/').next().unwrap_or_default();
+
+        if comm.trim() == name || executable == name {
+            if let Ok(details) = read_details(pid, clock, &users) {
+                found.push(details);
+            }
+            if found.len() >= MAX_FIND_RESULTS {
+                break;
+            }
+        }
+    }
+    Ok(found)
+}
+
+// Имена пользователей по UID
+pub type Users = HashMap<String, String>;
+
+// Имена пользователей из /etc/passwd; читаются один раз на запрос, а не для каждого процесса.
+// Без /etc/passwd владелец процесса сообщается номером UID
+pub fn read_users() -> Users {
+    fs::read_to_string("/etc/passwd").map(|passwd| parse_users(&passwd)).unwrap_or_default()
+}
+
+// Строки passwd вида "name:password:uid:..."; при повторе UID действует первая строка, как в getpwuid
+fn parse_users(passwd: &str) -> Users {
+    let mut users = Users::new();
+    for line in passwd.lines() {
+        let mut fields = line.split(':');
+        if let (Some(name), Some(uid)) = (fields.next(), fields.nth(1)) {
+            users.entry(uid.to_string()).or_insert_with(|| name.to_string());
+        }
+    }
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_command_line_is_truncated_on_char_boundary() {
+        assert_eq!(truncate("sleep 30".to_string(), 16), "sleep 30");
+        let truncated = truncate("x".repeat(2000), MAX_COMMAND_LINE_LEN);
+        assert_eq!(truncated.len(), MAX_COMMAND_LINE_LEN);
+        assert!(truncated.ends_with('…'));
+        // Двухбайтовые символы не разрезаются
+        let truncated = truncate("я".repeat(10), 8);
+        assert_eq!(truncated, "яя…");
+    }
+
+    // /proc/<pid>/stat процесса с пробелом и скобкой в имени
+    const STAT: &str = "4242 (my (weird) app) S 1 4242 4242 0 -1 4194560 1234 0 5 0 250 75 0 0 20 0 7 0 360000 123456789 2048 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0 0 0 0\n";
+
+    #[test]
+    fn stat_fields_follow_process_name() {
+        let stat = Stat::parse(STAT).unwrap();
+        assert!(stat.field(3).is_err(), "состояние - не число");
+        assert_eq!(stat.field(4).unwrap(), 1); // ppid
+        assert_eq!(stat.field(14).unwrap(), 250); // utime
+        assert_eq!(stat.field(15).unwrap(), 75); // stime
+        assert_eq!(stat.field(20).unwrap(), 7); // num_threads
+        assert_eq!(stat.field(22).unwrap(), 360_000); // starttime
+        assert!(stat.field(100).is_err());
+        assert!(Stat::parse("4242 no-parenthesis S 1").is_err());
+    }
+
+    #[test]
+    fn start_time_is_counted_from_boot() {
+        let stat = Stat::parse(STAT).unwrap();
+        let clock = SystemClock { clock_ticks: 100, boot_time: 1_700_000_000 };
+        assert_eq!(stat.start_time_ms(&clock).unwrap(), 1_700_000_000_000 + 3_600_000);
+    }
+
+    #[test]
+    fn status_values_are_found_by_whole_key() {
+        let status = "Name:\tbash\nState:\tS (sleeping)\nUid:\t1000\t1000\t1000\t1000\nVmSize:\t   10240 kB\nVmRSS:\t    5120 kB\n";
+        assert_eq!(status_value(status, "Name"), Some("bash"));
+        assert_eq!(status_value(status, "State"), Some("S (sleeping)"));
+        assert_eq!(status_value(status, "Vm"), None);
+        assert_eq!(status_bytes(status, "VmRSS"), 5120 * 1024);
+        assert_eq!(status_bytes(status, "VmSize"), 10240 * 1024);
+        // У потоков ядра строк о памяти нет
+        assert_eq!(status_bytes("Name:\tkthreadd\n", "VmRSS"), 0);
+    }
+
+    #[test]
+    fn users_are_parsed_from_passwd() {
+        let users = parse_users("root:x:0:0:root:/root:/bin/bash\nbroken\ntoor:x:0:0::/root:/bin/sh\nuser:x:1000:1000::/home/user:/bin/bash\n");
+        assert_eq!(users.get("0").map(String::as_str), Some("root"));
+        assert_eq!(users.get("1000").map(String::as_str), Some("user"));
+        assert_eq!(users.len(), 2);
+    }
+}
@@ -0,0 +1,66 @@
+// Поиск процессов с длинными командными строками: ответ помещается в один кадр,
+// соединение после него продолжает работать
+#![cfg(target_os = "linux")]
+
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+
+use protocol::{Request, Response, PROTOCOL_VERSION};
+use test_support::{connect, request, ServerProcess};
+
+const SERVER_ADDR: &str = "127.0.0.1:17881";
+
+// Процессы оболочки, ждущие ввода; завершаются вместе с тестом
+struct Sleepers(Vec<Child>);
+
+impl Sleepers {
+    // count процессов с именем name в argv[0] и аргументом длиной arg_len байт
+    fn spawn(name: &str, count: usize, arg_len: usize) -> Self {
+        let sleepers = (0..count)
+            .map(|_| {
+                Command::new("/bin/sh")
+                    .arg0(name)
+                    .args(["-c", "read _", "sh", &"x".repeat(arg_len)])
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .unwrap()
+            })
+            .collect();
+        Sleepers(sleepers)
+    }
+}
+
+impl Drop for Sleepers {
+    fn drop(&mut self) {
+        for child in &mut self.0 {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[test]
+fn long_command_lines_fit_into_one_reply() {
+    let name = format!("sleeper{}", std::process::id());
+    let _sleepers = Sleepers::spawn(&name, 80, 3000);
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server2"));
+    command.env("SERVER2_LISTEN", SERVER_ADDR);
+    let _server = ServerProcess::spawn(command);
+
+    let mut stream = connect(SERVER_ADDR);
+    let hello = Request::Hello { version: PROTOCOL_VERSION, client_id: 1, server_kind: None, token: None };
+    assert!(matches!(request(&mut stream, &hello), Response::Hello { .. }));
+
+    match request(&mut stream, &Request::ProcessFind { name: name.clone() }) {
+        Response::Processes { processes } => {
+            // Все 80 строк по 3 КБ в кадр 64 КиБ не помещаются даже обрезанными: список сокращён
+            assert!(!processes.is_empty() && processes.len() < 80, "{}", processes.len());
+            for process in &processes {
+                assert!(process.command_line.starts_with(&name), "{}", process.command_line);
+                assert!(process.command_line.len() <= 1024 && process.command_line.ends_with('…'), "{}", process.command_line);
+            }
+        }
+        other => panic!("Ожидался список процессов, получено: {:?}", other),
+    }
+    assert_eq!(request(&mut stream, &Request::Ping), Response::Pong);
+}
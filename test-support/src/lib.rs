@@ -0,0 +1,107 @@
+// Общие части интеграционных тестов серверов: запуск процесса сервера, временные файлы,
+// подключение и обмен сообщениями
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use protocol::{read_message, write_message, Request, Response};
+
+// Серверы тестов слушают фиксированные порты, поэтому тесты одной программы выполняются по очереди
+static SERVER_PORTS: Mutex<()> = Mutex::new(());
+
+// Запущенный сервер; останавливается при завершении теста
+pub struct ServerProcess {
+    child: Child,
+    _ports: MutexGuard<'static, ()>,
+}
+
+impl ServerProcess {
+    // Запуск после завершения серверов предыдущих тестов. Без заданного каталога сервер
+    // запускается во временном, чтобы его журнал не попадал в репозиторий
+    pub fn spawn(mut command: Command) -> Self {
+        let ports = SERVER_PORTS.lock().unwrap_or_else(PoisonError::into_inner);
+        if command.get_current_dir().is_none() {
+            command.current_dir(std::env::temp_dir());
+        }
+        let child = command.spawn().unwrap_or_else(|e| panic!("Не удалось запустить {:?}: {}", command.get_program(), e));
+        ServerProcess { child, _ports: ports }
+    }
+
+    // PID процесса сервера
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    // Код завершения, если сервер уже завершился (например, не смог занять порт)
+    pub fn exit_status(&mut self) -> Option<ExitStatus> {
+        self.child.try_wait().ok().flatten()
+    }
+
+    // Остановка сервера и его вывод в stderr (сервер должен быть запущен с перехватом stderr)
+    pub fn stop_with_stderr(mut self) -> String {
+        let _ = self.child.kill();
+        let mut stderr = String::new();
+        self.child.stderr.take().expect("stderr не перехвачен").read_to_string(&mut stderr).unwrap();
+        stderr
+    }
+}
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Временный каталог теста, удаляемый вместе с содержимым по завершении теста
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    // Имя включает PID, поэтому одновременные запуски тестов не мешают друг другу
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    // Файл в каталоге с заданным содержимым
+    pub fn file(&self, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// Подключение к запускающемуся серверу: попытки в течение 5 с
+pub fn connect(addr: &str) -> TcpStream {
+    for _ in 0..50 {
+        if let Ok(stream) = TcpStream::connect(addr) {
+            stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            return stream;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    panic!("Сервер не принял подключение на {}", addr);
+}
+
+// Запрос и ответ на него
+pub fn request<S: Read + Write>(stream: &mut S, request: &Request) -> Response {
+    write_message(stream, request).unwrap();
+    read_message(stream).unwrap().expect("Сервер закрыл соединение")
+}